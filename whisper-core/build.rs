@@ -1,26 +1,55 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Set to skip generating the C header, e.g. when building as a Rust-only dependency.
+///
+/// The `skip-bindgen` feature does the same for dependents that can set
+/// features but not the build environment.
+const SKIP_BINDGEN_ENV: &str = "WHISPER_CORE_SKIP_BINDGEN";
 
 fn main() {
+    println!("cargo:rerun-if-changed=src/");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed={}", SKIP_BINDGEN_ENV);
+
+    let skip_env = env::var_os(SKIP_BINDGEN_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+    if skip_env || env::var_os("CARGO_FEATURE_SKIP_BINDGEN").is_some() {
+        return;
+    }
+
     // Generate C header for Swift FFI
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let package_name = env::var("CARGO_PKG_NAME").unwrap();
-    let output_file = PathBuf::from(&crate_dir)
-        .join("include")
-        .join(format!("{}.h", package_name.replace('-', "_")));
+    let include_dir = PathBuf::from(&crate_dir).join("include");
+    let output_file = include_dir.join(format!("{}.h", package_name.replace('-', "_")));
 
-    // Create include directory if it doesn't exist
-    std::fs::create_dir_all(PathBuf::from(&crate_dir).join("include")).unwrap();
+    // The library builds without the header, so every failure only warns
+    if let Err(e) = generate_header(&crate_dir, &include_dir, &output_file) {
+        println!(
+            "cargo:warning=Unable to generate C header {} ({}); set {}=1 to skip it",
+            output_file.display(),
+            e,
+            SKIP_BINDGEN_ENV
+        );
+    }
+}
 
+/// Writes the C header to `output_file`, leaving it untouched if unchanged.
+fn generate_header(crate_dir: &str, include_dir: &Path, output_file: &Path) -> Result<(), String> {
     let config = cbindgen::Config::from_file("cbindgen.toml").unwrap_or_default();
-
-    cbindgen::Builder::new()
+    let bindings = cbindgen::Builder::new()
         .with_crate(crate_dir)
         .with_config(config)
         .generate()
-        .expect("Unable to generate bindings")
-        .write_to_file(&output_file);
+        .map_err(|e| e.to_string())?;
 
-    println!("cargo:rerun-if-changed=src/");
-    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    if std::fs::read(output_file).is_ok_and(|existing| existing == header) {
+        return Ok(());
+    }
+
+    // Read-only source trees, as in vendored or registry builds, fail here
+    std::fs::create_dir_all(include_dir).map_err(|e| e.to_string())?;
+    std::fs::write(output_file, header).map_err(|e| e.to_string())
 }
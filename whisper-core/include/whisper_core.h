@@ -1,102 +1,782 @@
-#include <cstdarg>
-#include <cstdint>
-#include <cstdlib>
-#include <ostream>
-#include <new>
-
-/// Audio sample rate expected by Whisper (16kHz).
-constexpr static const uint32_t WHISPER_SAMPLE_RATE = 16000;
-
-/// Result codes for FFI functions.
-enum class WhisperResultCode {
-  /// Operation succeeded.
-  Success = 0,
-  /// Generic error.
-  Error = -1,
-  /// Model not found.
-  ModelNotFound = -2,
-  /// Engine not initialized.
-  NotInitialized = -3,
-  /// Invalid parameter.
-  InvalidParameter = -4,
-  /// Transcription failed.
-  TranscriptionFailed = -5,
-};
-
-/// C-compatible configuration.
-struct CWhisperConfig {
-  /// Path to the model file (null-terminated UTF-8).
+#ifndef WHISPER_CORE_H
+#define WHISPER_CORE_H
+
+/* Warning: This file is autogenerated by cbindgen. Do not modify manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Audio sample rate expected by Whisper (16kHz).
+ */
+#define WHISPER_SAMPLE_RATE 16000
+
+
+
+/**
+ * Upper bound of [`AudioBuffer::estimate_snr_db`].
+ */
+#define SNR_MAX_DB 100.0
+
+/**
+ * Sample rates below this are narrowband (e.g. 8kHz telephone audio).
+ */
+#define NARROWBAND_SAMPLE_RATE 16000
+
+/**
+ * Speaker key used for segments without a `speaker_id`.
+ */
+#define UNKNOWN_SPEAKER UINT32_MAX
+
+/**
+ * Sample formats accepted by the audio-ingest functions.
+ *
+ * Passed across the boundary as an `i32` code so unknown values from C can
+ * be rejected instead of being undefined behavior.
+ */
+typedef enum WhisperAudioFormat {
+  /**
+   * 32-bit float samples in [-1.0, 1.0].
+   */
+  F32 = 0,
+  /**
+   * Signed 16-bit PCM samples.
+   */
+  I16 = 1,
+} WhisperAudioFormat;
+
+/**
+ * Result codes for FFI functions.
+ */
+typedef enum WhisperResultCode {
+  /**
+   * Operation succeeded.
+   */
+  SUCCESS = 0,
+  /**
+   * Generic error.
+   */
+  ERROR = -1,
+  /**
+   * Model not found.
+   */
+  MODEL_NOT_FOUND = -2,
+  /**
+   * Engine not initialized.
+   */
+  NOT_INITIALIZED = -3,
+  /**
+   * Invalid parameter.
+   */
+  INVALID_PARAMETER = -4,
+  /**
+   * Transcription failed.
+   */
+  TRANSCRIPTION_FAILED = -5,
+  /**
+   * The engine is in use by another call.
+   */
+  BUSY = -6,
+  /**
+   * A caller-provided buffer is too small; the required size was reported.
+   */
+  BUFFER_TOO_SMALL = -7,
+} WhisperResultCode;
+
+/**
+ * Opaque handle to an independently owned transcription engine.
+ *
+ * Created with `whisper_create` and released with `whisper_destroy`. Unlike the
+ * global API, any number of handles can be alive at once.
+ */
+typedef struct WhisperHandle WhisperHandle;
+
+/**
+ * C-compatible configuration.
+ */
+typedef struct CWhisperConfig {
+  /**
+   * Path to the model file (null-terminated UTF-8).
+   */
   const char *model_path;
-  /// Model size enum value.
+  /**
+   * Model size enum value.
+   */
   int32_t model_size;
-  /// Language code (null-terminated UTF-8).
+  /**
+   * Language code (null-terminated UTF-8).
+   */
   const char *language;
-  /// Whether to translate to English.
+  /**
+   * Whether to translate to English.
+   */
   bool translate;
-  /// Number of threads (0 = auto).
+  /**
+   * Number of threads (0 = auto).
+   */
   uint32_t n_threads;
-  /// Enable GPU acceleration.
+  /**
+   * Enable GPU acceleration.
+   */
   bool use_gpu;
-};
+} CWhisperConfig;
 
-/// C-compatible transcription result.
-struct CTranscriptionResult {
-  /// Transcribed text (null-terminated UTF-8).
+/**
+ * C-compatible transcription result.
+ */
+typedef struct CTranscriptionResult {
+  /**
+   * Transcribed text (null-terminated UTF-8).
+   */
   char *text;
-  /// Detected language code.
+  /**
+   * Detected language code.
+   */
   char *language;
-  /// Number of segments.
+  /**
+   * Number of segments.
+   */
   int32_t segment_count;
-  /// Processing time in milliseconds.
+  /**
+   * Processing time in milliseconds.
+   */
   uint64_t processing_time_ms;
-  /// Audio duration in milliseconds.
+  /**
+   * Part of `processing_time_ms` spent resampling.
+   */
+  uint64_t resample_time_ms;
+  /**
+   * Part of `processing_time_ms` spent decoding.
+   */
+  uint64_t inference_time_ms;
+  /**
+   * Audio duration in milliseconds.
+   */
   uint64_t audio_duration_ms;
-  /// Result code.
-  WhisperResultCode result_code;
-  /// Error message if result_code != Success.
+  /**
+   * Duration of the audio actually decoded after VAD trimming, in milliseconds.
+   */
+  uint64_t processed_duration_ms;
+  /**
+   * Whether `text` was cut at the configured `max_result_chars` limit.
+   */
+  bool text_truncated;
+  /**
+   * Whether segments were dropped at the configured `max_segments` limit.
+   */
+  bool segments_truncated;
+  /**
+   * Whether the audio had speech-level energy; set with empty `text`, the
+   * model produced nothing for audible input.
+   */
+  bool had_speech;
+  /**
+   * Whether decoding stopped at the configured `max_processing_ms` budget,
+   * leaving only the segments finished in time.
+   */
+  bool timed_out;
+  /**
+   * Whether beam search decoded nothing and the text comes from the
+   * configured `greedy_fallback` retry.
+   */
+  bool greedy_fallback;
+  /**
+   * Segment confidence averaged weighted by duration (0.0 without segments).
+   */
+  float average_confidence;
+  /**
+   * Result code.
+   */
+  enum WhisperResultCode result_code;
+  /**
+   * Error message if result_code != Success.
+   */
   char *error_message;
-};
+} CTranscriptionResult;
+
+/**
+ * C-compatible WAV properties, filled by `whisper_probe_wav`.
+ */
+typedef struct CAudioInfo {
+  /**
+   * Sample rate in Hz.
+   */
+  uint32_t sample_rate;
+  /**
+   * Number of interleaved channels.
+   */
+  uint16_t channels;
+  /**
+   * Bits per sample.
+   */
+  uint16_t bits_per_sample;
+  /**
+   * Duration in milliseconds.
+   */
+  uint64_t duration_ms;
+} CAudioInfo;
+
+/**
+ * C-compatible model file metadata, filled by `whisper_inspect_model`.
+ */
+typedef struct CModelMetadata {
+  /**
+   * Model size enum value, or -1 if the architecture is not recognized.
+   */
+  int32_t model_size;
+  /**
+   * Whether the model supports languages other than English.
+   */
+  bool multilingual;
+  /**
+   * Number of vocabulary tokens.
+   */
+  int32_t n_vocab;
+  /**
+   * Number of audio encoder layers.
+   */
+  int32_t n_audio_layer;
+  /**
+   * Number of text decoder layers.
+   */
+  int32_t n_text_layer;
+  /**
+   * Number of mel frequency bins.
+   */
+  int32_t n_mels;
+  /**
+   * Raw ggml weight type.
+   */
+  int32_t ftype;
+  /**
+   * Weight format name (null-terminated), e.g. "f16" or "q5_0".
+   */
+  char quantization[16];
+} CModelMetadata;
 
+/**
+ * C-compatible segment of the last transcription, filled by `whisper_get_segment`.
+ */
+typedef struct CSegment {
+  /**
+   * Start time in milliseconds.
+   */
+  int64_t start_ms;
+  /**
+   * End time in milliseconds.
+   */
+  int64_t end_ms;
+  /**
+   * Segment text (null-terminated UTF-8); free with `whisper_free_string`.
+   */
+  char *text;
+  /**
+   * Mean token probability (0.0 - 1.0).
+   */
+  float confidence;
+  /**
+   * Speaker or channel index, or -1 if unknown.
+   */
+  int32_t speaker_id;
+} CSegment;
+
+/**
+ * C-compatible word of a segment, filled by `whisper_get_segment_words`.
+ */
+typedef struct CWord {
+  /**
+   * Start time in milliseconds.
+   */
+  int64_t start_ms;
+  /**
+   * End time in milliseconds.
+   */
+  int64_t end_ms;
+  /**
+   * Word text (null-terminated UTF-8); free with `whisper_free_string`.
+   */
+  char *text;
+  /**
+   * Mean token probability (0.0 - 1.0).
+   */
+  float probability;
+} CWord;
+
+#ifdef __cplusplus
 extern "C" {
+#endif // __cplusplus
+
+/**
+ * Initializes the Whisper engine with the given configuration.
+ *
+ * # Safety
+ * The `config` pointer must be valid and properly initialized.
+ */
+enum WhisperResultCode whisper_init(const struct CWhisperConfig *config);
+
+/**
+ * Initializes the Whisper engine with default configuration.
+ */
+enum WhisperResultCode whisper_init_default(void);
+
+/**
+ * Initializes the Whisper engine from a JSON-serialized `WhisperConfig`.
+ *
+ * Fields missing from the JSON take their default values, so new config
+ * options never require changes to the C ABI.
+ *
+ * # Safety
+ * The `json` pointer must be a valid null-terminated UTF-8 string.
+ */
+enum WhisperResultCode whisper_init_from_json(const char *json);
+
+/**
+ * Initializes the Whisper engine like `whisper_init`, also returning the
+ * error message on failure.
+ *
+ * On failure `*out_error` receives an owned copy of the message, to be
+ * released with `whisper_free_string`; on success it is set to null.
+ *
+ * # Safety
+ * - The `config` pointer must be valid and properly initialized.
+ * - `out_error` must be null or point to writable memory for a pointer.
+ */
+enum WhisperResultCode whisper_init_checked(const struct CWhisperConfig *config, char **out_error);
+
+/**
+ * Frees a string returned by the library, such as from `whisper_init_checked`.
+ *
+ * # Safety
+ * `s` must be null or a string returned by this library and not yet freed.
+ */
+void whisper_free_string(char *s);
+
+/**
+ * Creates an independent engine with the given configuration.
+ *
+ * Returns null if the configuration is invalid or the model fails to load;
+ * `whisper_last_error` then describes why.
+ *
+ * # Safety
+ * - The `config` pointer must be valid and properly initialized.
+ * - The returned handle must be released with `whisper_destroy`.
+ */
+struct WhisperHandle *whisper_create(const struct CWhisperConfig *config);
+
+/**
+ * Transcribes interleaved audio samples with the engine behind `handle`.
+ *
+ * Channels are averaged to mono before transcription. A handle may be used
+ * from several threads at once, but must not be destroyed while a
+ * transcription on it is in progress.
+ *
+ * # Safety
+ * - `handle` must have been returned by `whisper_create` and not yet destroyed.
+ * - `samples` must be a valid pointer to `sample_count` f32 values
+ *   (`sample_count` counts samples across all channels).
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_handle(const struct WhisperHandle *handle,
+                                                      const float *samples,
+                                                      uintptr_t sample_count,
+                                                      uint32_t sample_rate,
+                                                      uint16_t channels);
+
+/**
+ * Transcribes interleaved samples in any `WhisperAudioFormat` with the
+ * engine behind `handle`.
+ *
+ * # Safety
+ * - `handle` must have been returned by `whisper_create` and not yet destroyed.
+ * - `samples` must be a valid pointer to `sample_count` values of the type
+ *   named by `format` (`sample_count` counts samples across all channels).
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_handle_samples(const struct WhisperHandle *handle,
+                                                              const void *samples,
+                                                              uintptr_t sample_count,
+                                                              int32_t format,
+                                                              uint32_t sample_rate,
+                                                              uint16_t channels);
+
+/**
+ * Destroys an engine created with `whisper_create`.
+ *
+ * # Safety
+ * `handle` must have been returned by `whisper_create` and not yet destroyed.
+ * Passing null is a no-op.
+ */
+void whisper_destroy(struct WhisperHandle *handle);
+
+/**
+ * Starts a live transcription session with its own engine, which calls
+ * `on_partial` with the transcript so far as audio is pushed.
+ *
+ * Only one session runs at a time; starting another while one is active
+ * fails with `Busy`. The callback runs on the thread calling
+ * `whisper_stream_push` or `whisper_stream_stop`, must not call the other
+ * `whisper_stream_*` functions, and must not keep the result, which is
+ * freed when it returns.
+ *
+ * # Safety
+ * - The `config` pointer must be valid and properly initialized.
+ * - `user_data` is passed to `on_partial` as is, and must stay usable from
+ *   the pushing threads until `whisper_stream_stop` returns.
+ */
+enum WhisperResultCode whisper_stream_start(const struct CWhisperConfig *config,
+                                            void (*on_partial)(const struct CTranscriptionResult*,
+                                                               void*),
+                                            void *user_data);
+
+/**
+ * Adds mono samples at `sample_rate` to the running stream.
+ *
+ * Once half a second of audio has arrived since the last partial result,
+ * the stream is transcribed and `on_partial` called before returning.
+ * Fails with `NotInitialized` if no stream is running.
+ *
+ * # Safety
+ * `samples` must be a valid pointer to `sample_count` f32 values.
+ */
+enum WhisperResultCode whisper_stream_push(const float *samples,
+                                           uintptr_t sample_count,
+                                           uint32_t sample_rate);
 
-/// Initializes the Whisper engine with the given configuration.
-///
-/// # Safety
-/// The `config` pointer must be valid and properly initialized.
-WhisperResultCode whisper_init(const CWhisperConfig *config);
-
-/// Initializes the Whisper engine with default configuration.
-WhisperResultCode whisper_init_default();
-
-/// Transcribes audio samples.
-///
-/// # Safety
-/// - `samples` must be a valid pointer to `sample_count` f32 values.
-/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
-CTranscriptionResult whisper_transcribe(const float *samples,
-                                        uintptr_t sample_count,
-                                        uint32_t sample_rate);
-
-/// Transcribes audio from a file.
-///
-/// # Safety
-/// - `file_path` must be a valid null-terminated UTF-8 string.
-/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
-CTranscriptionResult whisper_transcribe_file(const char *file_path);
-
-/// Frees a transcription result.
-///
-/// # Safety
-/// The `result` pointer must be valid and have been returned by a whisper_transcribe* function.
-void whisper_free_result(CTranscriptionResult *result);
-
-/// Shuts down the Whisper engine and releases resources.
-void whisper_shutdown();
-
-/// Returns the library version.
-const char *whisper_version();
-
-/// Checks if the engine is initialized.
-bool whisper_is_initialized();
+/**
+ * Ends the running stream, transcribing any audio not yet committed and
+ * calling `on_partial` a last time with the final transcript, then releases
+ * the stream's engine.
+ *
+ * Fails with `NotInitialized` if no stream is running.
+ */
+enum WhisperResultCode whisper_stream_stop(void);
 
+/**
+ * Transcribes interleaved audio samples.
+ *
+ * Channels are averaged to mono before transcription; pass 1 for mono audio.
+ *
+ * # Safety
+ * - `samples` must be a valid pointer to `sample_count` f32 values
+ *   (`sample_count` counts samples across all channels).
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe(const float *samples,
+                                               uintptr_t sample_count,
+                                               uint32_t sample_rate,
+                                               uint16_t channels);
+
+/**
+ * Transcribes interleaved 16-bit PCM samples.
+ *
+ * Channels are averaged to mono before transcription.
+ *
+ * # Safety
+ * - `samples` must be a valid pointer to `sample_count` i16 values
+ *   (`sample_count` counts samples across all channels).
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_i16(const int16_t *samples,
+                                                   uintptr_t sample_count,
+                                                   uint32_t sample_rate,
+                                                   uint16_t channels);
+
+/**
+ * Transcribes interleaved samples in any `WhisperAudioFormat`.
+ *
+ * `format` selects how `samples` is read; channels are averaged to mono
+ * before transcription.
+ *
+ * # Safety
+ * - `samples` must be a valid pointer to `sample_count` values of the type
+ *   named by `format` (`sample_count` counts samples across all channels).
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_samples(const void *samples,
+                                                       uintptr_t sample_count,
+                                                       int32_t format,
+                                                       uint32_t sample_rate,
+                                                       uint16_t channels);
+
+/**
+ * Transcribes like `whisper_transcribe_samples`, but fails with `Busy`
+ * instead of waiting if the global engine is in use, e.g. by a running
+ * transcription.
+ *
+ * # Safety
+ * Same as `whisper_transcribe_samples`.
+ */
+struct CTranscriptionResult whisper_try_transcribe(const void *samples,
+                                                   uintptr_t sample_count,
+                                                   int32_t format,
+                                                   uint32_t sample_rate,
+                                                   uint16_t channels);
+
+/**
+ * Transcribes audio from a file.
+ *
+ * On Unix the path is used as raw bytes and need not be valid UTF-8.
+ *
+ * # Safety
+ * - `file_path` must be a valid null-terminated string.
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_file(const char *file_path);
+
+/**
+ * Transcribes audio from a file whose path is given as `path_len` bytes,
+ * without a terminator.
+ *
+ * On Unix the bytes are used as-is, so paths that are not valid UTF-8 work
+ * (pass e.g. `FileManager.fileSystemRepresentation` from Swift).
+ *
+ * # Safety
+ * - `path` must be a valid pointer to `path_len` bytes.
+ * - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+ */
+struct CTranscriptionResult whisper_transcribe_file_bytes(const uint8_t *path, uintptr_t path_len);
+
+/**
+ * Transcribes `in_path` with the global engine and writes SubRip subtitles
+ * to `out_path`, replacing any existing file.
+ *
+ * # Safety
+ * `in_path` and `out_path` must be valid null-terminated strings.
+ */
+enum WhisperResultCode whisper_transcribe_file_to_srt(const char *in_path, const char *out_path);
+
+/**
+ * Transcribes `in_path` with the global engine and writes WebVTT subtitles
+ * to `out_path`, replacing any existing file.
+ *
+ * # Safety
+ * `in_path` and `out_path` must be valid null-terminated strings.
+ */
+enum WhisperResultCode whisper_transcribe_file_to_vtt(const char *in_path, const char *out_path);
+
+/**
+ * Reads the header of in-memory WAV data into `info` without decoding samples.
+ *
+ * Returns `InvalidParameter` if the data is not a readable WAV header;
+ * `whisper_last_error` then has details.
+ *
+ * # Safety
+ * - `data` must point to `len` readable bytes.
+ * - `info` must point to writable memory for a `CAudioInfo`.
+ */
+enum WhisperResultCode whisper_probe_wav(const uint8_t *data,
+                                         uintptr_t len,
+                                         struct CAudioInfo *info);
+
+/**
+ * Reads a model file's header into `metadata` without loading the model.
+ *
+ * Returns `ModelNotFound` if the file does not exist and `Error` if it is not
+ * a whisper ggml model; `whisper_last_error` then has details.
+ *
+ * # Safety
+ * - `path` must be a valid null-terminated UTF-8 string.
+ * - `metadata` must point to writable memory for a `CModelMetadata`.
+ */
+enum WhisperResultCode whisper_inspect_model(const char *path, struct CModelMetadata *metadata);
+
+/**
+ * Computes the RMS energy envelope of mono `samples` for visualization.
+ *
+ * `format` is a `WhisperAudioFormat` code. Frames are `window_ms` long and
+ * start every `hop_ms`. The total frame count is stored in `out_len`, and
+ * as many frames as fit are written to `out`. Pass a null `out` to query
+ * the required capacity. Zero samples give an empty envelope.
+ *
+ * # Safety
+ * - `samples` must be a valid pointer to `sample_count` values of the type
+ *   named by `format`.
+ * - `out`, if not null, must point to writable memory for `out_capacity` f32 values.
+ * - `out_len` must point to writable memory for a `usize`.
+ */
+enum WhisperResultCode whisper_energy_envelope(const void *samples,
+                                               uintptr_t sample_count,
+                                               int32_t format,
+                                               uint32_t sample_rate,
+                                               uint32_t window_ms,
+                                               uint32_t hop_ms,
+                                               float *out,
+                                               uintptr_t out_capacity,
+                                               uintptr_t *out_len);
+
+/**
+ * Frees a transcription result.
+ *
+ * # Safety
+ * The `result` pointer must be valid and have been returned by a whisper_transcribe* function.
+ */
+void whisper_free_result(struct CTranscriptionResult *result);
+
+/**
+ * Returns the number of segments in the last transcription of the global
+ * engine, or -1 if there is none (not initialized, or cleared with
+ * `whisper_clear_result`).
+ */
+int32_t whisper_get_segment_count(void);
+
+/**
+ * Copies segment `index` of the last transcription of the global engine
+ * into `segment`.
+ *
+ * Returns `NotInitialized` if there is no stored result, including after
+ * `whisper_clear_result`, and `InvalidParameter` if `index` is out of range.
+ *
+ * # Safety
+ * - `segment` must point to writable memory for a `CSegment`.
+ * - On success, `segment->text` must be freed with `whisper_free_string`.
+ */
+enum WhisperResultCode whisper_get_segment(uintptr_t index, struct CSegment *segment);
+
+/**
+ * Copies the words of segment `index` of the last transcription of the
+ * global engine into `words`, which has room for `max_words` entries.
+ *
+ * `count` always receives the segment's word count (0 unless the engine was
+ * configured with word timestamps). If it exceeds `max_words`, nothing is
+ * copied and `BufferTooSmall` is returned, so passing 0 queries the size.
+ * Other failures are as for `whisper_get_segment`.
+ *
+ * # Safety
+ * - `words` must point to writable memory for `max_words` `CWord`s, or be
+ *   null when `max_words` is 0.
+ * - `count` must point to writable memory for a `usize`.
+ * - On success, each `words[i].text` must be freed with `whisper_free_string`.
+ */
+enum WhisperResultCode whisper_get_segment_words(uintptr_t index,
+                                                 struct CWord *words,
+                                                 uintptr_t max_words,
+                                                 uintptr_t *count);
+
+/**
+ * Frees the global engine's stored last transcription.
+ *
+ * Segment accessors then report no result until the next transcription.
+ * Safe to call when nothing is stored or the engine is not initialized.
+ */
+void whisper_clear_result(void);
+
+/**
+ * Shuts down the Whisper engine and releases resources.
+ */
+void whisper_shutdown(void);
+
+/**
+ * Returns the library version.
+ */
+const char *whisper_version(void);
+
+/**
+ * Estimates the processing time in milliseconds for `sample_count` samples
+ * at `sample_rate`, without transcribing.
+ *
+ * Returns 0 if the engine is not initialized or the sample rate is 0.
+ */
+uint64_t whisper_estimate_time_ms(uintptr_t sample_count, uint32_t sample_rate);
+
+/**
+ * Returns how long the global engine took to load its model, in milliseconds.
+ *
+ * Returns 0 if the engine is not initialized.
+ */
+uint64_t whisper_model_load_time_ms(void);
+
+/**
+ * Returns how long the engine behind `handle` took to load its model, in milliseconds.
+ *
+ * Returns 0 if `handle` is null.
+ *
+ * # Safety
+ * `handle` must be null or have been returned by `whisper_create` and not yet destroyed.
+ */
+uint64_t whisper_handle_model_load_time_ms(const struct WhisperHandle *handle);
+
+/**
+ * Unloads the global engine's model after `timeout_ms` of inactivity
+ * (0 = keep it resident). It is reloaded transparently on the next transcription.
+ */
+enum WhisperResultCode whisper_set_idle_unload(uint64_t timeout_ms);
+
+/**
+ * Unloads the model of the engine behind `handle` after `timeout_ms` of
+ * inactivity (0 = keep it resident).
+ *
+ * # Safety
+ * `handle` must be null or have been returned by `whisper_create` and not yet destroyed.
+ */
+enum WhisperResultCode whisper_handle_set_idle_unload(const struct WhisperHandle *handle,
+                                                      uint64_t timeout_ms);
+
+/**
+ * Returns the message of the most recent failed FFI call on the calling thread,
+ * or null if none has failed.
+ *
+ * The string is owned by the library and must not be freed. It stays valid
+ * until the next failing FFI call on the same thread replaces it; copy it
+ * if it is needed longer.
+ */
+const char *whisper_last_error(void);
+
+/**
+ * Sets the directory where models are looked up by default, for every
+ * engine in the process. Pass null to restore the default directory.
+ *
+ * Returns `InvalidParameter`, keeping the current directory, if `path` is
+ * not an existing directory.
+ *
+ * # Safety
+ * `path` must be null or a valid null-terminated string.
+ */
+enum WhisperResultCode whisper_set_models_dir(const char *path);
+
+/**
+ * Checks the model files for `count` model sizes, writing one status per
+ * size to `out_codes`, without loading any weights.
+ *
+ * Models are looked up in `dir`, or in the default models directory if
+ * `dir` is null. A status is `Success` for a valid model, `ModelNotFound`
+ * for a missing file, `InvalidParameter` for an unknown size code and
+ * `Error` for a file that is not a whisper ggml model.
+ *
+ * # Safety
+ * - `sizes` must point to `count` model size codes.
+ * - `dir` must be null or a valid null-terminated string.
+ * - `out_codes` must point to writable memory for `count` `WhisperResultCode`s.
+ */
+enum WhisperResultCode whisper_preload_models(const int32_t *sizes,
+                                              uintptr_t count,
+                                              const char *dir,
+                                              enum WhisperResultCode *out_codes);
+
+/**
+ * Checks whether the model file for `model_size` exists at its default path,
+ * without loading it.
+ *
+ * Returns false for unknown sizes or if the models directory cannot be determined.
+ */
+bool whisper_model_available(int32_t model_size);
+
+/**
+ * Checks if the engine is initialized.
+ */
+bool whisper_is_initialized(void);
+
+/**
+ * Checks if the loaded model supports languages other than English.
+ *
+ * Returns false for English-only (`.en`) models and when the engine is not
+ * initialized.
+ */
+bool whisper_is_model_multilingual(void);
+
+#ifdef __cplusplus
 } // extern "C"
+#endif // __cplusplus
+
+#endif /* WHISPER_CORE_H */
@@ -1,11 +1,32 @@
 //! Audio capture and processing utilities.
 
 use crate::error::{Result, WhisperError};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Audio sample rate expected by Whisper (16kHz).
 pub const WHISPER_SAMPLE_RATE: u32 = 16000;
 
+/// Sample rates accepted for input audio.
+pub const SUPPORTED_SAMPLE_RATES: std::ops::RangeInclusive<u32> = 8000..=192_000;
+
+/// Checks that `sample_rate` is within [`SUPPORTED_SAMPLE_RATES`].
+pub fn validate_sample_rate(sample_rate: u32) -> Result<()> {
+    if SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+        Ok(())
+    } else {
+        Err(WhisperError::AudioError(format!(
+            "Unsupported sample rate {}Hz (expected {}-{}Hz)",
+            sample_rate,
+            SUPPORTED_SAMPLE_RATES.start(),
+            SUPPORTED_SAMPLE_RATES.end()
+        )))
+    }
+}
+
 /// Audio format for Whisper processing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AudioFormat {
@@ -15,6 +36,26 @@ pub enum AudioFormat {
     F32,
 }
 
+/// Resampling algorithm, trading CPU cost for quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ResampleQuality {
+    /// Linear interpolation between neighbouring samples. About 2 multiply-adds
+    /// per output sample; no anti-aliasing, so downsampling folds high frequencies
+    /// back into the speech band.
+    Linear,
+    /// Catmull-Rom cubic interpolation over 4 samples. About 4x the cost of
+    /// `Linear`, smoother but still without anti-aliasing.
+    #[default]
+    Cubic,
+    /// Blackman-windowed sinc with 16 zero crossings and a low-pass cutoff at the
+    /// lower Nyquist frequency. Roughly 32 taps per output sample, times the
+    /// downsampling factor (~100 taps for 48kHz to 16kHz); best quality.
+    ///
+    /// Common rate pairs such as 48kHz or 44.1kHz to 16kHz use the same filter
+    /// precomputed for the exact ratio, which is several times faster.
+    SincHigh,
+}
+
 /// Audio buffer for storing recorded samples.
 #[derive(Debug, Clone)]
 pub struct AudioBuffer {
@@ -49,6 +90,20 @@ impl AudioBuffer {
         }
     }
 
+    /// Creates a mono buffer from interleaved f32 samples, averaging channels.
+    ///
+    /// `channels` of 1 is equivalent to [`from_samples`](Self::from_samples).
+    pub fn from_interleaved(samples: &[f32], channels: u16, sample_rate: u32) -> Self {
+        let mono = downmix_interleaved(samples, channels, |s| s);
+        Self::from_samples(mono, sample_rate)
+    }
+
+    /// Creates a mono buffer from interleaved 16-bit PCM, averaging channels.
+    pub fn from_i16_interleaved(samples: &[i16], channels: u16, sample_rate: u32) -> Self {
+        let mono = downmix_interleaved(samples, channels, |s| s as f32 / 32768.0);
+        Self::from_samples(mono, sample_rate)
+    }
+
     /// Returns the samples as a slice.
     pub fn samples(&self) -> &[f32] {
         &self.samples
@@ -59,8 +114,11 @@ impl AudioBuffer {
         self.sample_rate
     }
 
-    /// Returns the duration in seconds.
+    /// Returns the duration in seconds (0.0 if the sample rate is zero).
     pub fn duration_seconds(&self) -> f32 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
         self.samples.len() as f32 / self.sample_rate as f32
     }
 
@@ -79,31 +137,106 @@ impl AudioBuffer {
         self.samples.extend_from_slice(samples);
     }
 
+    /// Concatenates buffers into one.
+    ///
+    /// All buffers must share the same sample rate. An empty slice yields an
+    /// empty buffer at Whisper's sample rate.
+    pub fn concat(buffers: &[AudioBuffer]) -> Result<AudioBuffer> {
+        let sample_rate = match common_sample_rate(buffers)? {
+            Some(rate) => rate,
+            None => return Ok(AudioBuffer::new()),
+        };
+
+        let total_len = buffers.iter().map(|b| b.len()).sum();
+        let mut samples = Vec::with_capacity(total_len);
+        for buffer in buffers {
+            samples.extend_from_slice(&buffer.samples);
+        }
+
+        Ok(AudioBuffer::from_samples(samples, sample_rate))
+    }
+
+    /// Concatenates buffers, overlapping each seam by `crossfade_ms` and
+    /// blending linearly from one buffer into the next to avoid clicks.
+    ///
+    /// The result is shorter than [`concat`](Self::concat) by one overlap per
+    /// seam; an overlap never exceeds either buffer it joins. All buffers must
+    /// share the same sample rate.
+    pub fn concat_crossfade(buffers: &[AudioBuffer], crossfade_ms: u32) -> Result<AudioBuffer> {
+        let sample_rate = match common_sample_rate(buffers)? {
+            Some(rate) => rate,
+            None => return Ok(AudioBuffer::new()),
+        };
+        let crossfade = (crossfade_ms as u64 * sample_rate as u64 / 1000) as usize;
+
+        let mut samples: Vec<f32> = Vec::with_capacity(buffers.iter().map(|b| b.len()).sum());
+        for buffer in buffers {
+            let overlap = crossfade.min(samples.len()).min(buffer.len());
+            let start = samples.len() - overlap;
+            for (i, (out, &incoming)) in samples[start..].iter_mut().zip(&buffer.samples).enumerate() {
+                let weight = (i + 1) as f32 / (overlap + 1) as f32;
+                *out = *out * (1.0 - weight) + incoming * weight;
+            }
+            samples.extend_from_slice(&buffer.samples[overlap..]);
+        }
+
+        Ok(AudioBuffer::from_samples(samples, sample_rate))
+    }
+
+    /// Pads with silence or truncates so the buffer lasts exactly `target_ms`
+    /// (rounded down to whole samples).
+    ///
+    /// Both happen at the end, so existing samples keep their timestamps.
+    /// Negative durations empty the buffer.
+    pub fn fit_to_duration(&mut self, target_ms: i64) {
+        let target_len = (target_ms.max(0) as u64 * self.sample_rate as u64 / 1000) as usize;
+        self.samples.resize(target_len, 0.0);
+    }
+
     /// Clears all samples from the buffer.
     pub fn clear(&mut self) {
         self.samples.clear();
     }
 
     /// Resamples the audio to the target sample rate if needed.
+    ///
+    /// Uses [`ResampleQuality::Cubic`]; see [`resample_with`](Self::resample_with).
     pub fn resample(&self, target_rate: u32) -> Result<AudioBuffer> {
+        self.resample_with(target_rate, ResampleQuality::default())
+    }
+
+    /// Resamples the audio to the target sample rate with the given algorithm.
+    ///
+    /// Both rates must be within [`SUPPORTED_SAMPLE_RATES`].
+    pub fn resample_with(&self, target_rate: u32, quality: ResampleQuality) -> Result<AudioBuffer> {
+        validate_sample_rate(self.sample_rate)?;
+        validate_sample_rate(target_rate)?;
+
         if self.sample_rate == target_rate {
             return Ok(self.clone());
         }
 
-        let ratio = target_rate as f64 / self.sample_rate as f64;
-        let new_len = (self.samples.len() as f64 * ratio) as usize;
-        let mut resampled = Vec::with_capacity(new_len);
+        if self.samples.is_empty() {
+            return Ok(AudioBuffer::from_samples(Vec::new(), target_rate));
+        }
 
-        for i in 0..new_len {
-            let src_idx = i as f64 / ratio;
-            let idx_floor = src_idx.floor() as usize;
-            let idx_ceil = (idx_floor + 1).min(self.samples.len() - 1);
-            let frac = src_idx - idx_floor as f64;
+        let ratio = target_rate as f64 / self.sample_rate as f64;
+        // Round rather than truncate so duration is preserved to within half a sample
+        let new_len = (self.samples.len() as f64 * ratio).round() as usize;
 
-            let sample = self.samples[idx_floor] as f64 * (1.0 - frac)
-                + self.samples[idx_ceil] as f64 * frac;
-            resampled.push(sample as f32);
-        }
+        let resampled = match quality {
+            ResampleQuality::Linear => resample_linear(&self.samples, ratio, new_len),
+            ResampleQuality::Cubic => resample_cubic(&self.samples, ratio, new_len),
+            ResampleQuality::SincHigh => {
+                let divisor = gcd(self.sample_rate, target_rate);
+                let (up, down) = (target_rate / divisor, self.sample_rate / divisor);
+                if up <= MAX_POLYPHASE_PHASES {
+                    resample_polyphase(&self.samples, up as usize, down as usize, new_len)
+                } else {
+                    resample_sinc(&self.samples, ratio, new_len)
+                }
+            }
+        };
 
         Ok(AudioBuffer::from_samples(resampled, target_rate))
     }
@@ -136,6 +269,196 @@ impl AudioBuffer {
         }
     }
 
+    /// Returns the RMS level of the whole buffer (0.0 when empty).
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        (self.samples.iter().map(|s| s * s).sum::<f32>() / self.samples.len() as f32).sqrt()
+    }
+
+    /// Scales the audio so its RMS level is `target_rms`, with the gain
+    /// limited to `max_gain`, and returns the gain applied.
+    ///
+    /// Samples are clamped to [-1.0, 1.0] afterwards. Silent buffers are left
+    /// unchanged (gain 1.0).
+    pub fn normalize_rms(&mut self, target_rms: f32, max_gain: f32) -> f32 {
+        let rms = self.rms();
+        if rms <= 0.0 {
+            return 1.0;
+        }
+
+        let gain = (target_rms / rms).min(max_gain);
+        for sample in &mut self.samples {
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+        gain
+    }
+
+    /// Compresses the dynamic range with a feed-forward compressor.
+    ///
+    /// Wherever the signal's peak envelope rises above `threshold_db` (dBFS),
+    /// the excess is divided by `ratio`, so 1.0 leaves the audio unchanged.
+    /// The envelope follows peaks instantly and decays over
+    /// about 100ms, so gain does not pump within a syllable.
+    pub fn compress_dynamics(&mut self, threshold_db: f32, ratio: f32) {
+        if ratio <= 1.0 {
+            return;
+        }
+
+        let release = (-1.0 / (COMPRESSOR_RELEASE_MS * self.sample_rate as f32 / 1000.0)).exp();
+        let mut envelope = 0.0f32;
+        for sample in &mut self.samples {
+            envelope = sample.abs().max(envelope * release);
+            if envelope <= 0.0 {
+                continue;
+            }
+
+            let level_db = 20.0 * envelope.log10();
+            if level_db > threshold_db {
+                let gain_db = (threshold_db - level_db) * (1.0 - 1.0 / ratio);
+                *sample *= 10f32.powf(gain_db / 20.0);
+            }
+        }
+    }
+
+    /// Applies linear fade-in and fade-out ramps to soften hard cuts.
+    ///
+    /// Each ramp is clamped to the buffer length; when they overlap, both apply.
+    pub fn fade(&mut self, fade_in_ms: u32, fade_out_ms: u32) {
+        let len = self.samples.len();
+        let to_samples = |ms: u32| ((ms as u64 * self.sample_rate as u64 / 1000) as usize).min(len);
+        let (fade_in, fade_out) = (to_samples(fade_in_ms), to_samples(fade_out_ms));
+
+        for (i, sample) in self.samples.iter_mut().take(fade_in).enumerate() {
+            *sample *= i as f32 / fade_in as f32;
+        }
+        for (i, sample) in self.samples.iter_mut().rev().take(fade_out).enumerate() {
+            *sample *= i as f32 / fade_out as f32;
+        }
+    }
+
+    /// Band-limits the audio to the speech range (about 80Hz-8kHz).
+    ///
+    /// The lower edge is a 4th-order Butterworth filter built from two
+    /// cascaded biquads. The upper edge is 8th order, four biquads, so hiss
+    /// half an octave above the band is already cut by over 20dB; it is
+    /// lowered below Nyquist for low sample rates.
+    pub fn speech_bandpass(&mut self) {
+        if self.samples.is_empty() || self.sample_rate == 0 {
+            return;
+        }
+
+        let rate = self.sample_rate as f64;
+        let high_cutoff = SPEECH_BAND_HIGH_HZ.min(rate * 0.45);
+        let mut stages = [
+            Biquad::highpass(rate, SPEECH_BAND_LOW_HZ, BUTTERWORTH_Q[0]),
+            Biquad::highpass(rate, SPEECH_BAND_LOW_HZ, BUTTERWORTH_Q[1]),
+            Biquad::lowpass(rate, high_cutoff, BUTTERWORTH_8_Q[0]),
+            Biquad::lowpass(rate, high_cutoff, BUTTERWORTH_8_Q[1]),
+            Biquad::lowpass(rate, high_cutoff, BUTTERWORTH_8_Q[2]),
+            Biquad::lowpass(rate, high_cutoff, BUTTERWORTH_8_Q[3]),
+        ];
+
+        for sample in &mut self.samples {
+            let mut x = *sample as f64;
+            for stage in &mut stages {
+                x = stage.process(x);
+            }
+            *sample = x as f32;
+        }
+    }
+
+    /// Returns the RMS energy of frames `window_ms` long, starting every `hop_ms`.
+    ///
+    /// The last frames are shortened at the end of the buffer rather than
+    /// zero-padded. Returns an empty envelope if either duration is 0.
+    pub fn energy_envelope(&self, window_ms: u32, hop_ms: u32) -> Vec<f32> {
+        if window_ms == 0 || hop_ms == 0 {
+            return Vec::new();
+        }
+
+        let to_samples = |ms: u32| ((ms as u64 * self.sample_rate as u64 / 1000) as usize).max(1);
+        let (window, hop) = (to_samples(window_ms), to_samples(hop_ms));
+
+        (0..self.samples.len())
+            .step_by(hop)
+            .map(|start| {
+                let frame = &self.samples[start..(start + window).min(self.samples.len())];
+                (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+            })
+            .collect()
+    }
+
+    /// Returns the millisecond ranges that are silent for at least `min_silence_ms`.
+    ///
+    /// `threshold` has the same meaning as the VAD threshold, so a region here
+    /// is audio that VAD trimming would treat as non-speech. Long silences make
+    /// natural paragraph breaks in long-form transcripts.
+    pub fn silence_regions(&self, min_silence_ms: u32, threshold: f32) -> Vec<(i64, i64)> {
+        let to_ms = |sample: usize| (sample as u64 * 1000 / self.sample_rate.max(1) as u64) as i64;
+        crate::vad::silence_regions(&self.samples, self.sample_rate, threshold, min_silence_ms)
+            .into_iter()
+            .map(|range| (to_ms(range.start), to_ms(range.end)))
+            .collect()
+    }
+
+    /// Splits the buffer in the middle of each silence of at least
+    /// `min_silence_ms`, returning each chunk with its start offset in
+    /// milliseconds.
+    ///
+    /// Silence at the very start or end is not a split point, so no chunk is
+    /// pure lead-in or tail. The chunks together cover the whole buffer, and
+    /// adding a chunk's offset to its timestamps maps them back to this buffer.
+    pub fn split_on_silence(&self, min_silence_ms: u32, threshold: f32) -> Vec<(i64, AudioBuffer)> {
+        if self.samples.is_empty() {
+            return Vec::new();
+        }
+
+        let mut cuts: Vec<usize> = crate::vad::silence_regions(&self.samples, self.sample_rate, threshold, min_silence_ms)
+            .into_iter()
+            .filter(|range| range.start > 0 && range.end < self.samples.len())
+            .map(|range| range.start + range.len() / 2)
+            .collect();
+        cuts.push(self.samples.len());
+
+        let to_ms = |sample: usize| (sample as u64 * 1000 / self.sample_rate.max(1) as u64) as i64;
+        let mut start = 0;
+        cuts.into_iter()
+            .map(|end| {
+                let chunk = AudioBuffer::from_samples(self.samples[start..end].to_vec(), self.sample_rate);
+                let offset_ms = to_ms(start);
+                start = end;
+                (offset_ms, chunk)
+            })
+            .collect()
+    }
+
+    /// Estimates the signal-to-noise ratio in decibels.
+    ///
+    /// Audio is split into 30ms frames; the loudest fifth are taken as speech
+    /// and the quietest fifth as noise. Returns [`SNR_SILENT_DB`] if the buffer
+    /// holds no signal, and at most [`SNR_MAX_DB`] if the noise is digital silence.
+    pub fn estimate_snr_db(&self) -> f32 {
+        let frame_len = (self.sample_rate as usize * SNR_FRAME_MS / 1000).max(1);
+        let mut energies: Vec<f64> = self
+            .samples
+            .chunks(frame_len)
+            .map(|frame| frame.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>() / frame.len() as f64)
+            .collect();
+        energies.sort_by(|a, b| a.total_cmp(b));
+
+        let share = energies.len().div_ceil(5);
+        let mean = |frames: &[f64]| frames.iter().sum::<f64>() / frames.len().max(1) as f64;
+        let noise = mean(&energies[..share]);
+        let signal = mean(&energies[energies.len() - share..]);
+
+        if signal <= 0.0 {
+            return SNR_SILENT_DB;
+        }
+        ((10.0 * (signal / noise).log10()) as f32).min(SNR_MAX_DB)
+    }
+
     /// Applies a simple noise gate.
     pub fn apply_noise_gate(&mut self, threshold: f32) {
         for sample in &mut self.samples {
@@ -152,6 +475,254 @@ impl Default for AudioBuffer {
     }
 }
 
+/// Returns the sample rate every buffer shares, or `None` for no buffers.
+fn common_sample_rate(buffers: &[AudioBuffer]) -> Result<Option<u32>> {
+    let sample_rate = match buffers.first() {
+        Some(first) => first.sample_rate,
+        None => return Ok(None),
+    };
+
+    if let Some((index, mismatched)) = buffers
+        .iter()
+        .enumerate()
+        .find(|(_, b)| b.sample_rate != sample_rate)
+    {
+        return Err(WhisperError::AudioError(format!(
+            "Cannot concatenate buffers with different sample rates: buffer 0 is {}Hz, buffer {} is {}Hz",
+            sample_rate, index, mismatched.sample_rate
+        )));
+    }
+    Ok(Some(sample_rate))
+}
+
+/// Averages interleaved frames into mono samples, converting each with `to_f32`.
+///
+/// A trailing partial frame is dropped. `channels` of 0 is treated as mono.
+fn downmix_interleaved<T: Copy>(samples: &[T], channels: u16, to_f32: impl Fn(T) -> f32) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    if channels == 1 {
+        return samples.iter().map(|&s| to_f32(s)).collect();
+    }
+
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().map(|&s| to_f32(s)).sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Returned by [`AudioBuffer::estimate_snr_db`] for buffers without any signal.
+pub const SNR_SILENT_DB: f32 = f32::NEG_INFINITY;
+
+/// Upper bound of [`AudioBuffer::estimate_snr_db`].
+pub const SNR_MAX_DB: f32 = 100.0;
+
+/// Frame length used to estimate the signal-to-noise ratio.
+const SNR_FRAME_MS: usize = 30;
+
+/// Time for the compressor's envelope to decay by 1/e after a peak.
+const COMPRESSOR_RELEASE_MS: f32 = 100.0;
+
+/// Lower edge of the speech band.
+const SPEECH_BAND_LOW_HZ: f64 = 80.0;
+
+/// Upper edge of the speech band.
+const SPEECH_BAND_HIGH_HZ: f64 = 8000.0;
+
+/// Q factors of the two sections of a 4th-order Butterworth filter.
+const BUTTERWORTH_Q: [f64; 2] = [0.541_196_1, 1.306_563];
+
+/// Q factors of the four sections of an 8th-order Butterworth filter.
+const BUTTERWORTH_8_Q: [f64; 4] = [0.509_795_6, 0.601_344_9, 0.899_976_2, 2.562_915_4];
+
+/// Second-order IIR section (RBJ cookbook), transposed direct form II.
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn highpass(sample_rate: f64, cutoff: f64, q: f64) -> Self {
+        let (cos, alpha) = Self::prewarp(sample_rate, cutoff, q);
+        let b0 = (1.0 + cos) / 2.0;
+        Self::normalized(b0, -(1.0 + cos), b0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    fn lowpass(sample_rate: f64, cutoff: f64, q: f64) -> Self {
+        let (cos, alpha) = Self::prewarp(sample_rate, cutoff, q);
+        let b0 = (1.0 - cos) / 2.0;
+        Self::normalized(b0, 1.0 - cos, b0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    fn prewarp(sample_rate: f64, cutoff: f64, q: f64) -> (f64, f64) {
+        let w0 = std::f64::consts::TAU * cutoff / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// Linear interpolation resampler. `ratio` is target rate / source rate.
+fn resample_linear(samples: &[f32], ratio: f64, new_len: usize) -> Vec<f32> {
+    let mut resampled = Vec::with_capacity(new_len);
+
+    for i in 0..new_len {
+        let src_idx = i as f64 / ratio;
+        let idx_floor = src_idx.floor() as usize;
+        let idx_ceil = (idx_floor + 1).min(samples.len() - 1);
+        let frac = src_idx - idx_floor as f64;
+
+        let sample = samples[idx_floor] as f64 * (1.0 - frac) + samples[idx_ceil] as f64 * frac;
+        resampled.push(sample as f32);
+    }
+
+    resampled
+}
+
+/// Catmull-Rom cubic interpolation resampler.
+fn resample_cubic(samples: &[f32], ratio: f64, new_len: usize) -> Vec<f32> {
+    let last = samples.len() as isize - 1;
+    let at = |idx: isize| samples[idx.clamp(0, last) as usize] as f64;
+    let mut resampled = Vec::with_capacity(new_len);
+
+    for i in 0..new_len {
+        let src_idx = i as f64 / ratio;
+        let base = src_idx.floor() as isize;
+        let t = src_idx - base as f64;
+
+        let (p0, p1, p2, p3) = (at(base - 1), at(base), at(base + 1), at(base + 2));
+        let sample = p1
+            + 0.5 * t * (p2 - p0
+                + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3
+                    + t * (3.0 * (p1 - p2) + p3 - p0)));
+        resampled.push(sample as f32);
+    }
+
+    resampled
+}
+
+/// Number of sinc zero crossings on each side of the windowed-sinc kernel.
+const SINC_ZERO_CROSSINGS: f64 = 16.0;
+
+/// Blackman-windowed sinc resampler with anti-aliasing low-pass.
+fn resample_sinc(samples: &[f32], ratio: f64, new_len: usize) -> Vec<f32> {
+    use std::f64::consts::PI;
+
+    // When downsampling, lower the cutoff to the target Nyquist frequency
+    let cutoff = ratio.min(1.0);
+    let half_width = SINC_ZERO_CROSSINGS / cutoff;
+    let last = samples.len() - 1;
+    let mut resampled = Vec::with_capacity(new_len);
+
+    for i in 0..new_len {
+        let center = i as f64 / ratio;
+        let start = (center - half_width).ceil().max(0.0) as usize;
+        let end = ((center + half_width).floor() as usize).min(last);
+
+        let mut acc = 0.0;
+        for (k, &sample) in samples.iter().enumerate().take(end + 1).skip(start) {
+            let x = k as f64 - center;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * cutoff * x).sin() / (PI * cutoff * x)
+            };
+            let w = x / half_width;
+            let window = 0.42 + 0.5 * (PI * w).cos() + 0.08 * (2.0 * PI * w).cos();
+            acc += sample as f64 * cutoff * sinc * window;
+        }
+        resampled.push(acc as f32);
+    }
+
+    resampled
+}
+
+/// Largest interpolation factor for which [`resample_polyphase`] precomputes
+/// its filter; 16kHz from 44.1kHz needs 160 phases, from 22.05kHz 320.
+const MAX_POLYPHASE_PHASES: u32 = 480;
+
+/// Greatest common divisor of two sample rates.
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Windowed-sinc resampler for a rational ratio `up` / `down` (in lowest
+/// terms), with the same kernel as [`resample_sinc`] but tabulated once per
+/// phase instead of evaluated for every tap.
+fn resample_polyphase(samples: &[f32], up: usize, down: usize, new_len: usize) -> Vec<f32> {
+    use std::f64::consts::PI;
+
+    let cutoff = (up as f64 / down as f64).min(1.0);
+    let half_width = SINC_ZERO_CROSSINGS / cutoff;
+
+    // Taps for each fractional position, as offsets from the preceding source sample
+    let phases: Vec<(isize, Vec<f32>)> = (0..up)
+        .map(|phase| {
+            let frac = phase as f64 / up as f64;
+            let first = (frac - half_width).ceil() as isize;
+            let last = (frac + half_width).floor() as isize;
+            let taps = (first..=last)
+                .map(|offset| {
+                    let x = offset as f64 - frac;
+                    let sinc = if x == 0.0 {
+                        1.0
+                    } else {
+                        (PI * cutoff * x).sin() / (PI * cutoff * x)
+                    };
+                    let w = x / half_width;
+                    let window = 0.42 + 0.5 * (PI * w).cos() + 0.08 * (2.0 * PI * w).cos();
+                    (cutoff * sinc * window) as f32
+                })
+                .collect();
+            (first, taps)
+        })
+        .collect();
+
+    let len = samples.len() as isize;
+    let mut resampled = Vec::with_capacity(new_len);
+    for i in 0..new_len {
+        let position = i * down;
+        let (first, taps) = &phases[position % up];
+        let start = (position / up) as isize + first;
+
+        // Drop the taps that fall outside the signal, as resample_sinc does
+        let skip = (-start).max(0);
+        let take = (len - start).clamp(0, taps.len() as isize);
+        let acc: f64 = taps[skip as usize..take.max(skip) as usize]
+            .iter()
+            .zip(&samples[(start + skip) as usize..])
+            .map(|(&tap, &sample)| tap as f64 * sample as f64)
+            .sum();
+        resampled.push(acc as f32);
+    }
+
+    resampled
+}
+
 /// Thread-safe audio recorder state.
 pub struct AudioRecorderState {
     /// Whether recording is active.
@@ -169,6 +740,12 @@ impl AudioRecorderState {
     }
 }
 
+impl Default for AudioRecorderState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Shared state type for the audio recorder.
 pub type SharedRecorderState = Arc<Mutex<AudioRecorderState>>;
 
@@ -177,16 +754,83 @@ pub fn create_shared_state() -> SharedRecorderState {
     Arc::new(Mutex::new(AudioRecorderState::new()))
 }
 
+/// Sample rates below this are narrowband (e.g. 8kHz telephone audio).
+pub const NARROWBAND_SAMPLE_RATE: u32 = 16000;
+
+/// Codec an audio file was stored with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AudioCodec {
+    /// Uncompressed PCM in a WAV container.
+    Pcm,
+    /// IEEE float samples in a WAV container.
+    PcmFloat,
+    /// FLAC lossless compression.
+    Flac,
+    /// Opus in an OGG container.
+    Opus,
+}
+
+/// Properties of an audio file before it was converted for Whisper.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioMetadata {
+    /// Sample rate of the file.
+    ///
+    /// For Opus, which always decodes at 48kHz, this is the rate of the
+    /// encoder's input as recorded in the header, when present.
+    pub sample_rate: u32,
+    /// Number of channels.
+    pub channels: u16,
+    /// Bits per sample, or `None` for lossy codecs.
+    pub bits_per_sample: Option<u16>,
+    /// Duration in milliseconds.
+    pub duration_ms: u64,
+    /// Storage codec.
+    pub codec: AudioCodec,
+}
+
+impl AudioMetadata {
+    /// Returns whether the source was narrowband, like telephone audio,
+    /// which transcribes noticeably worse.
+    pub fn is_narrowband(&self) -> bool {
+        self.sample_rate < NARROWBAND_SAMPLE_RATE
+    }
+}
+
 /// Loads audio from a WAV file.
-pub fn load_wav_file(path: &str) -> Result<AudioBuffer> {
-    let reader = hound::WavReader::open(path)
-        .map_err(|e| WhisperError::IoError(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            e.to_string(),
-        )))?;
+pub fn load_wav_file(path: impl AsRef<Path>) -> Result<AudioBuffer> {
+    load_wav(path.as_ref()).map(|(buffer, _)| buffer)
+}
+
+/// Reads the properties of in-memory WAV data from its header, without
+/// decoding any samples.
+pub fn probe_wav(data: &[u8]) -> Result<AudioMetadata> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(data))
+        .map_err(|e| WhisperError::AudioError(format!("Invalid WAV header: {}", e)))?;
+    Ok(wav_metadata(&reader))
+}
+
+/// Describes a WAV stream from the header `reader` has parsed.
+fn wav_metadata<R: std::io::Read>(reader: &hound::WavReader<R>) -> AudioMetadata {
+    let spec = reader.spec();
+    AudioMetadata {
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        bits_per_sample: Some(spec.bits_per_sample),
+        duration_ms: reader.duration() as u64 * 1000 / spec.sample_rate.max(1) as u64,
+        codec: match spec.sample_format {
+            hound::SampleFormat::Float => AudioCodec::PcmFloat,
+            hound::SampleFormat::Int => AudioCodec::Pcm,
+        },
+    }
+}
+
+/// Loads a WAV file along with its original properties.
+fn load_wav(path: &Path) -> Result<(AudioBuffer, AudioMetadata)> {
+    let reader = hound::WavReader::open(path).map_err(wav_error)?;
 
     let spec = reader.spec();
     let sample_rate = spec.sample_rate;
+    let metadata = wav_metadata(&reader);
 
     let samples: Vec<f32> = match spec.sample_format {
         hound::SampleFormat::Float => {
@@ -205,22 +849,953 @@ pub fn load_wav_file(path: &str) -> Result<AudioBuffer> {
         }
     };
 
-    // Convert to mono if stereo
-    let mono_samples = if spec.channels == 2 {
-        samples
-            .chunks(2)
-            .map(|chunk| (chunk[0] + chunk.get(1).unwrap_or(&0.0)) / 2.0)
-            .collect()
-    } else {
-        samples
+    // Average all channels, however many there are, into mono
+    let buffer = AudioBuffer::from_interleaved(&samples, spec.channels, sample_rate);
+    Ok((buffer, metadata))
+}
+
+/// Opens a WAV file for reading in chunks of `chunk_seconds`, so files
+/// larger than memory can be processed piece by piece.
+///
+/// Each chunk is downmixed to mono and resampled to 16kHz with `quality` on
+/// its own, so resampling edge effects fall on chunk boundaries, where
+/// chunked transcription splits its windows anyway. The last chunk holds
+/// whatever audio remains.
+pub fn stream_wav_chunks(path: impl AsRef<Path>, chunk_seconds: f32, quality: ResampleQuality) -> Result<WavChunks> {
+    if !(chunk_seconds.is_finite() && chunk_seconds > 0.0) {
+        return Err(WhisperError::ConfigError(format!(
+            "Chunk length must be positive, got {}s",
+            chunk_seconds
+        )));
+    }
+
+    let reader = hound::WavReader::open(path.as_ref()).map_err(wav_error)?;
+    let spec = reader.spec();
+    validate_sample_rate(spec.sample_rate)?;
+
+    let frames = ((chunk_seconds as f64 * spec.sample_rate as f64).round() as usize).max(1);
+    Ok(WavChunks {
+        metadata: wav_metadata(&reader),
+        chunk_len: frames * spec.channels as usize,
+        int_scale: match spec.sample_format {
+            hound::SampleFormat::Float => None,
+            hound::SampleFormat::Int => Some((1i64 << (spec.bits_per_sample - 1)) as f32),
+        },
+        reader,
+        quality,
+        resample_time: Duration::ZERO,
+        finished: false,
+    })
+}
+
+/// Iterator over the chunks of a WAV file, from [`stream_wav_chunks`].
+pub struct WavChunks {
+    reader: hound::WavReader<std::io::BufReader<std::fs::File>>,
+    metadata: AudioMetadata,
+    /// Interleaved samples read per chunk.
+    chunk_len: usize,
+    /// Divisor mapping integer samples to [-1, 1]; `None` for float data.
+    int_scale: Option<f32>,
+    quality: ResampleQuality,
+    /// Time spent resampling the chunks read so far.
+    resample_time: Duration,
+    finished: bool,
+}
+
+impl WavChunks {
+    /// Returns the file's original properties.
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    /// Returns the time spent resampling the chunks read so far.
+    pub fn resample_time(&self) -> Duration {
+        self.resample_time
+    }
+}
+
+impl Iterator for WavChunks {
+    type Item = Result<AudioBuffer>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let read: std::result::Result<Vec<f32>, hound::Error> = match self.int_scale {
+            Some(scale) => self.reader
+                .samples::<i32>()
+                .take(self.chunk_len)
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect(),
+            None => self.reader.samples::<f32>().take(self.chunk_len).collect(),
+        };
+        let samples = match read {
+            Ok(samples) => samples,
+            Err(e) => {
+                self.finished = true;
+                return Some(Err(WhisperError::AudioError(format!("Failed to read WAV samples: {}", e))));
+            }
+        };
+
+        self.finished = samples.len() < self.chunk_len;
+        if samples.is_empty() {
+            return None;
+        }
+
+        let sample_rate = self.metadata.sample_rate;
+        let buffer = AudioBuffer::from_interleaved(&samples, self.metadata.channels, sample_rate);
+        if sample_rate == WHISPER_SAMPLE_RATE {
+            return Some(Ok(buffer));
+        }
+        let resample_start = Instant::now();
+        let resampled = buffer.resample_with(WHISPER_SAMPLE_RATE, self.quality);
+        self.resample_time += resample_start.elapsed();
+        Some(resampled)
+    }
+}
+
+/// Loads audio from a file, choosing the decoder from the file extension.
+///
+/// The audio is downmixed to mono but keeps the file's sample rate, so the
+/// caller picks the resampling quality. Opus in an OGG container (`.opus`, `.ogg`, `.oga`) is decoded with libopus,
+/// `.flac` with claxon; anything else is read as WAV.
+///
+/// Paths need not be valid UTF-8.
+pub fn load_audio_file(path: impl AsRef<Path>) -> Result<AudioBuffer> {
+    load_audio_with_metadata(path).map(|(buffer, _)| buffer)
+}
+
+/// Loads audio like [`load_audio_file`], also returning the file's original
+/// sample rate, channels, bit depth, duration and codec.
+pub fn load_audio_with_metadata(path: impl AsRef<Path>) -> Result<(AudioBuffer, AudioMetadata)> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "opus" | "ogg" | "oga" => decode_ogg_opus(std::io::BufReader::new(std::fs::File::open(path)?)),
+        "flac" => decode_flac(std::io::BufReader::new(std::fs::File::open(path)?)),
+        _ => load_wav(path),
+    }
+}
+
+/// Sample rate Opus streams are decoded at.
+const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Maximum Opus frame duration (120ms) in samples per channel at 48kHz.
+const OPUS_MAX_FRAME_SAMPLES: usize = 5760;
+
+/// Loads audio from an Opus-in-OGG file (e.g. voice messages).
+pub fn load_ogg_opus_file(path: impl AsRef<Path>) -> Result<AudioBuffer> {
+    let file = std::fs::File::open(path)?;
+    decode_ogg_opus(std::io::BufReader::new(file)).map(|(buffer, _)| buffer)
+}
+
+/// Decodes an Opus-in-OGG stream to a mono 48kHz buffer.
+fn decode_ogg_opus<R: Read + Seek>(reader: R) -> Result<(AudioBuffer, AudioMetadata)> {
+    let mut packets = ogg::PacketReader::new(reader);
+    let read_packet = |packets: &mut ogg::PacketReader<R>| {
+        packets
+            .read_packet()
+            .map_err(|e| WhisperError::AudioError(format!("Failed to read OGG packet: {}", e)))
+    };
+
+    // The first packet identifies the codec (RFC 7845, section 5.1)
+    let head = read_packet(&mut packets)?
+        .ok_or_else(|| WhisperError::AudioError("Empty OGG stream".to_string()))?;
+    if head.data.len() < 19 || !head.data.starts_with(b"OpusHead") {
+        return Err(WhisperError::UnsupportedFormat(
+            "OGG stream does not contain Opus audio".to_string(),
+        ));
+    }
+
+    let serial = head.stream_serial();
+    let channel_count = head.data[9] as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    let input_sample_rate = u32::from_le_bytes([head.data[12], head.data[13], head.data[14], head.data[15]]);
+    let mapping_family = head.data[18];
+
+    let channels = match (mapping_family, channel_count) {
+        (0, 1) => opus::Channels::Mono,
+        (0, 2) => opus::Channels::Stereo,
+        _ => {
+            return Err(WhisperError::UnsupportedFormat(format!(
+                "Opus channel mapping family {} with {} channels",
+                mapping_family, channel_count
+            )))
+        }
+    };
+
+    let mut decoder = opus::Decoder::new(OPUS_SAMPLE_RATE, channels)
+        .map_err(|e| WhisperError::AudioError(format!("Failed to create Opus decoder: {}", e)))?;
+
+    let mut frame = vec![0.0f32; OPUS_MAX_FRAME_SAMPLES * channel_count];
+    let mut samples = Vec::new();
+    let mut final_granule = None;
+    let mut tags_seen = false;
+
+    while let Some(packet) = read_packet(&mut packets)? {
+        if packet.stream_serial() != serial {
+            continue;
+        }
+
+        // The second packet carries comment tags, not audio
+        if !tags_seen {
+            tags_seen = true;
+            if packet.data.starts_with(b"OpusTags") {
+                continue;
+            }
+        }
+
+        // Frame sizes vary per packet; the decoder reports samples per channel
+        let decoded = decoder
+            .decode_float(&packet.data, &mut frame, false)
+            .map_err(|e| WhisperError::AudioError(format!("Failed to decode Opus packet: {}", e)))?;
+
+        for chunk in frame[..decoded * channel_count].chunks(channel_count) {
+            samples.push(chunk.iter().sum::<f32>() / channel_count as f32);
+        }
+
+        if packet.last_in_stream() {
+            final_granule = Some(packet.absgp_page() as usize);
+            break;
+        }
+    }
+
+    // Drop the encoder's pre-skip and any end padding indicated by the granule position
+    if let Some(granule) = final_granule {
+        samples.truncate(granule.max(pre_skip));
+    }
+    samples.drain(..pre_skip.min(samples.len()));
+
+    let metadata = AudioMetadata {
+        sample_rate: if input_sample_rate > 0 { input_sample_rate } else { OPUS_SAMPLE_RATE },
+        channels: channel_count as u16,
+        bits_per_sample: None,
+        duration_ms: samples.len() as u64 * 1000 / OPUS_SAMPLE_RATE as u64,
+        codec: AudioCodec::Opus,
+    };
+    Ok((AudioBuffer::from_samples(samples, OPUS_SAMPLE_RATE), metadata))
+}
+
+/// Loads audio from a FLAC file.
+pub fn load_flac_file(path: impl AsRef<Path>) -> Result<AudioBuffer> {
+    let file = std::fs::File::open(path)?;
+    decode_flac(std::io::BufReader::new(file)).map(|(buffer, _)| buffer)
+}
+
+/// Decodes a FLAC stream of any bit depth and channel count to a mono
+/// buffer at the stream's sample rate.
+fn decode_flac<R: Read>(reader: R) -> Result<(AudioBuffer, AudioMetadata)> {
+    let mut reader = claxon::FlacReader::new(reader).map_err(flac_error)?;
+    let info = reader.streaminfo();
+
+    // Samples are signed integers of `bits_per_sample` bits
+    let scale = 1.0 / (1u64 << (info.bits_per_sample - 1)) as f32;
+    let interleaved = reader
+        .samples()
+        .collect::<std::result::Result<Vec<i32>, _>>()
+        .map_err(flac_error)?;
+
+    let samples = downmix_interleaved(&interleaved, info.channels as u16, |s| s as f32 * scale);
+    let metadata = AudioMetadata {
+        sample_rate: info.sample_rate,
+        channels: info.channels as u16,
+        bits_per_sample: Some(info.bits_per_sample as u16),
+        duration_ms: samples.len() as u64 * 1000 / info.sample_rate.max(1) as u64,
+        codec: AudioCodec::Flac,
     };
+    Ok((AudioBuffer::from_samples(samples, info.sample_rate), metadata))
+}
+
+/// Maps a hound error to an I/O error, or an audio error for malformed
+/// files, such as hound's rejection of headers declaring no channels.
+fn wav_error(e: hound::Error) -> WhisperError {
+    match e {
+        hound::Error::IoError(e) => WhisperError::IoError(e),
+        e => WhisperError::AudioError(format!("Invalid WAV file: {}", e)),
+    }
+}
+
+fn flac_error(e: claxon::Error) -> WhisperError {
+    match e {
+        claxon::Error::IoError(e) => WhisperError::IoError(e),
+        claxon::Error::FormatError(msg) => {
+            WhisperError::UnsupportedFormat(format!("Invalid FLAC stream: {}", msg))
+        }
+        claxon::Error::Unsupported(msg) => {
+            WhisperError::UnsupportedFormat(format!("Unsupported FLAC feature: {}", msg))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::time::Instant;
+
+    /// MSB-first bit writer for building FLAC test streams.
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bits: u32,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u64, width: u32) {
+            for i in (0..width).rev() {
+                if self.bits.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                let bit = ((value >> i) & 1) as u8;
+                *self.bytes.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+                self.bits += 1;
+            }
+        }
+
+        fn align(&mut self) {
+            self.bits = self.bits.div_ceil(8) * 8;
+        }
+    }
+
+    fn crc(data: &[u8], poly: u16, width: u32) -> u16 {
+        let top = 1u32 << (width - 1);
+        let mask = ((1u32 << width) - 1) as u16;
+        let mut crc = 0u32;
+        for &byte in data {
+            crc ^= (byte as u32) << (width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 { (crc << 1) ^ poly as u32 } else { crc << 1 };
+            }
+        }
+        crc as u16 & mask
+    }
+
+    /// Encodes interleaved samples as a single-frame FLAC stream with verbatim subframes.
+    fn encode_flac(samples: &[i32], channels: u32, bits: u32, sample_rate: u32) -> Vec<u8> {
+        let block = samples.len() as u64 / channels as u64;
+        let mut w = BitWriter::default();
+        w.bytes.extend_from_slice(b"fLaC");
+        w.bits = 32;
+
+        // STREAMINFO, the last metadata block
+        w.write(1, 1);
+        w.write(0, 7);
+        w.write(34, 24);
+        w.write(16, 16);
+        w.write(block.max(16), 16);
+        w.write(0, 24);
+        w.write(0, 24);
+        w.write(sample_rate as u64, 20);
+        w.write(channels as u64 - 1, 3);
+        w.write(bits as u64 - 1, 5);
+        w.write(block, 36);
+        w.write(0, 64);
+        w.write(0, 64);
+
+        // Frame header: fixed blocking, 16-bit block size, rate from STREAMINFO
+        let depth_code = match bits {
+            16 => 0b100,
+            24 => 0b110,
+            _ => panic!("unsupported test bit depth {bits}"),
+        };
+        let frame_start = w.bytes.len();
+        w.write(0b11111111111110, 14);
+        w.write(0, 2);
+        w.write(0b0111, 4);
+        w.write(0, 4);
+        w.write(channels as u64 - 1, 4);
+        w.write(depth_code, 3);
+        w.write(0, 1);
+        w.write(0, 8);
+        w.write(block - 1, 16);
+        let header_crc = crc(&w.bytes[frame_start..], 0x07, 8);
+        w.write(header_crc as u64, 8);
+
+        for channel in 0..channels as usize {
+            w.write(0b00000010, 8);
+            for frame in samples.chunks_exact(channels as usize) {
+                w.write(frame[channel] as u64 & ((1 << bits) - 1), bits);
+            }
+        }
+        w.align();
+        let footer_crc = crc(&w.bytes[frame_start..], 0x8005, 16);
+        w.write(footer_crc as u64, 16);
+        w.bytes
+    }
+
+    #[test]
+    fn test_decode_flac_bit_depths() {
+        let half = encode_flac(&[16384, -16384, 0, 8192], 1, 16, 16000);
+        let buffer = decode_flac(Cursor::new(half)).unwrap().0;
+        assert_eq!(buffer.samples(), &[0.5, -0.5, 0.0, 0.25]);
+
+        let deep = encode_flac(&[4_194_304, -8_388_608], 1, 24, 16000);
+        let buffer = decode_flac(Cursor::new(deep)).unwrap().0;
+        assert_eq!(buffer.samples(), &[0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_decode_flac_stereo_downmix() {
+        let data = encode_flac(&[16384, 0, -32768, -32768, 8192, 8192], 2, 16, 16000);
+        let (buffer, metadata) = decode_flac(Cursor::new(data)).unwrap();
+        assert_eq!(buffer.samples(), &[0.25, -1.0, 0.25]);
+        assert_eq!((metadata.channels, metadata.bits_per_sample), (2, Some(16)));
+    }
+
+    #[test]
+    fn test_decode_flac_keeps_sample_rate() {
+        let samples: Vec<i32> = (0..44100).map(|i| (i % 200) * 100).collect();
+        let data = encode_flac(&samples, 1, 16, 44100);
+        let (buffer, metadata) = decode_flac(Cursor::new(data)).unwrap();
+        assert_eq!(buffer.sample_rate(), 44100);
+        assert_eq!(buffer.len(), 44100);
+        assert_eq!((metadata.sample_rate, metadata.duration_ms), (44100, 1000));
+        assert_eq!(metadata.codec, AudioCodec::Flac);
+    }
+
+    // APFS rejects file names that are not valid UTF-8
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_load_audio_file_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = std::ffi::OsStr::from_bytes(b"whisper-core-\xff\xfe-test.flac");
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, encode_flac(&[16384, -16384], 1, 16, 16000)).unwrap();
+
+        let buffer = load_audio_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(buffer.unwrap().samples(), &[0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_load_audio_with_metadata_wav() {
+        let path = std::env::temp_dir().join("whisper-core-metadata-test.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..8000 {
+            writer.write_sample(1000i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let loaded = load_audio_with_metadata(&path);
+        std::fs::remove_file(&path).unwrap();
+        let (buffer, metadata) = loaded.unwrap();
+        assert_eq!((buffer.sample_rate(), buffer.len()), (8000, 4000));
+        assert_eq!(metadata.duration_ms, 500);
+        assert_eq!((metadata.channels, metadata.codec), (2, AudioCodec::Pcm));
+        assert!(metadata.is_narrowband());
+    }
+
+    #[test]
+    fn test_load_wav_multichannel() {
+        let path = std::env::temp_dir().join("whisper-core-quad-test.wav");
+        let spec = hound::WavSpec {
+            channels: 4,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..1000 {
+            for value in [16384i16, 0, -8192, 8192] {
+                writer.write_sample(value).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+
+        let loaded = load_wav_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        let buffer = loaded.unwrap();
+        assert_eq!(buffer.len(), 1000);
+        assert!(buffer.samples().iter().all(|&s| (s - 0.125).abs() < 1e-6));
+    }
 
-    let mut buffer = AudioBuffer::from_samples(mono_samples, sample_rate);
-    
-    // Resample to Whisper's expected rate if needed
-    if sample_rate != WHISPER_SAMPLE_RATE {
-        buffer = buffer.resample(WHISPER_SAMPLE_RATE)?;
+    #[test]
+    fn test_stream_wav_chunks() {
+        let path = std::env::temp_dir().join("whisper-core-stream-test.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..20000 {
+            writer.write_sample(8192i16).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let chunks = stream_wav_chunks(&path, 1.0, ResampleQuality::Cubic).unwrap();
+        assert_eq!(chunks.metadata().duration_ms, 2500);
+        let chunks: Vec<AudioBuffer> = chunks.collect::<Result<_>>().unwrap();
+        let invalid = stream_wav_chunks(&path, 0.0, ResampleQuality::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let lengths: Vec<usize> = chunks.iter().map(|c| c.len()).collect();
+        assert_eq!(lengths, vec![16000, 16000, 8000]);
+        assert!(chunks.iter().all(|c| c.sample_rate() == WHISPER_SAMPLE_RATE));
+        assert!((chunks[1].samples()[8000] - 0.125).abs() < 1e-3);
+        assert!(matches!(invalid, Err(WhisperError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_load_wav_zero_channels() {
+        // 44-byte PCM header declaring 0 channels
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&36u32.to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        header.extend_from_slice(&0u16.to_le_bytes()); // channels
+        header.extend_from_slice(&16000u32.to_le_bytes());
+        header.extend_from_slice(&32000u32.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+
+        let path = std::env::temp_dir().join("whisper-core-no-channels-test.wav");
+        std::fs::write(&path, header).unwrap();
+        let loaded = load_wav_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(loaded, Err(WhisperError::AudioError(_))), "{:?}", loaded.err());
+    }
+
+    #[test]
+    fn test_probe_wav() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut data, spec).unwrap();
+        for _ in 0..88200 {
+            writer.write_sample(0i32).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let metadata = probe_wav(data.get_ref()).unwrap();
+        assert_eq!((metadata.sample_rate, metadata.channels), (44100, 1));
+        assert_eq!(metadata.bits_per_sample, Some(24));
+        assert_eq!(metadata.duration_ms, 2000);
+        assert!(matches!(probe_wav(b"not a wav file"), Err(WhisperError::AudioError(_))));
+    }
+
+    #[test]
+    fn test_decode_flac_invalid() {
+        let err = decode_flac(Cursor::new(b"RIFF0000WAVE".to_vec())).unwrap_err();
+        assert!(matches!(err, WhisperError::UnsupportedFormat(_)));
+    }
+
+    /// Encodes `seconds` of a 440Hz tone as an Opus-in-OGG stream.
+    fn encode_ogg_opus(seconds: f32, channels: opus::Channels) -> Vec<u8> {
+        let channel_count = channels as usize;
+        let pre_skip: u16 = 312;
+        let mut encoder =
+            opus::Encoder::new(OPUS_SAMPLE_RATE, channels, opus::Application::Voip).unwrap();
+
+        let mut out = Vec::new();
+        let mut writer = ogg::PacketWriter::new(&mut out);
+
+        let mut head = b"OpusHead".to_vec();
+        head.push(1);
+        head.push(channel_count as u8);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        writer.write_packet(head, 1, ogg::PacketWriteEndInfo::EndPage, 0).unwrap();
+        writer
+            .write_packet(b"OpusTags".to_vec(), 1, ogg::PacketWriteEndInfo::EndPage, 0)
+            .unwrap();
+
+        // Like real encoders, cover the pre-skip so the whole input survives trimming
+        let total = (seconds * OPUS_SAMPLE_RATE as f32) as usize + pre_skip as usize;
+        // Alternate 20ms and 40ms frames to exercise variable frame sizes
+        let frame_sizes = [960, 1920];
+        let mut position = 0;
+        let mut index = 0;
+        while position < total {
+            let frame_size = frame_sizes[index % 2];
+            index += 1;
+            let input: Vec<f32> = (position..position + frame_size)
+                .flat_map(|i| {
+                    let t = i as f32 / OPUS_SAMPLE_RATE as f32;
+                    let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.5;
+                    std::iter::repeat_n(sample, channel_count)
+                })
+                .collect();
+            let packet = encoder.encode_vec_float(&input, 4000).unwrap();
+            position += frame_size;
+            let end = if position >= total {
+                ogg::PacketWriteEndInfo::EndStream
+            } else {
+                ogg::PacketWriteEndInfo::NormalPacket
+            };
+            let granule = position.min(total) as u64;
+            writer.write_packet(packet, 1, end, granule).unwrap();
+        }
+
+        drop(writer);
+        out
+    }
+
+    #[test]
+    fn test_from_interleaved() {
+        let mono = AudioBuffer::from_interleaved(&[0.5, -0.5], 1, 16000);
+        assert_eq!(mono.samples(), &[0.5, -0.5]);
+
+        let stereo = AudioBuffer::from_interleaved(&[0.5, 0.0, -1.0, -0.5, 0.25], 2, 16000);
+        assert_eq!(stereo.samples(), &[0.25, -0.75]);
+        assert_eq!(stereo.duration_seconds(), 2.0 / 16000.0);
+    }
+
+    #[test]
+    fn test_from_i16_interleaved() {
+        let mono = AudioBuffer::from_i16_interleaved(&[16384, -16384], 1, 16000);
+        assert_eq!(mono.samples(), &[0.5, -0.5]);
+
+        let stereo = AudioBuffer::from_i16_interleaved(&[16384, 0, -32768, -32768, 1], 2, 48000);
+        assert_eq!(stereo.samples(), &[0.25, -1.0]);
+        assert_eq!(stereo.sample_rate(), 48000);
+    }
+
+    #[test]
+    fn test_invalid_sample_rates() {
+        let zero = AudioBuffer::from_samples(vec![0.0; 100], 0);
+        assert_eq!(zero.duration_seconds(), 0.0);
+        assert!(matches!(zero.resample(WHISPER_SAMPLE_RATE), Err(WhisperError::AudioError(_))));
+
+        let absurd = AudioBuffer::from_samples(vec![0.0; 100], 10_000_000);
+        assert!(absurd.resample(WHISPER_SAMPLE_RATE).is_err());
+
+        let valid = AudioBuffer::from_samples(vec![0.0; 100], 8000);
+        assert!(valid.resample(0).is_err());
+        assert!(validate_sample_rate(8000).is_ok());
+        assert!(validate_sample_rate(192_000).is_ok());
+        assert!(validate_sample_rate(7999).is_err());
+    }
+
+    #[test]
+    fn test_fit_to_duration() {
+        // Shorter input is padded with silence at the end
+        let mut short = AudioBuffer::from_samples(vec![0.5; 8000], 16000);
+        short.fit_to_duration(1000);
+        assert_eq!(short.len(), 16000);
+        assert_eq!(short.samples()[7999], 0.5);
+        assert!(short.samples()[8000..].iter().all(|&s| s == 0.0));
+
+        // Longer input is truncated at the end
+        let mut long = AudioBuffer::from_samples((0..48000).map(|i| i as f32).collect(), 16000);
+        long.fit_to_duration(250);
+        assert_eq!(long.len(), 4000);
+        assert_eq!(long.samples()[3999], 3999.0);
+
+        long.fit_to_duration(-5);
+        assert!(long.is_empty());
+    }
+
+    #[test]
+    fn test_fade() {
+        let mut buffer = AudioBuffer::from_samples(vec![1.0; 10], 1000);
+        buffer.fade(4, 2);
+        assert_eq!(buffer.samples(), &[0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0, 1.0, 0.5, 0.0]);
+
+        // Ramps longer than the buffer are clamped
+        let mut short = AudioBuffer::from_samples(vec![1.0; 4], 1000);
+        short.fade(100, 0);
+        assert_eq!(short.samples(), &[0.0, 0.25, 0.5, 0.75]);
+
+        let mut empty = AudioBuffer::new();
+        empty.fade(10, 10);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_concat() {
+        let a = AudioBuffer::from_samples(vec![0.1, 0.2], 16000);
+        let b = AudioBuffer::from_samples(vec![0.3], 16000);
+        let joined = AudioBuffer::concat(&[a.clone(), b]).unwrap();
+        assert_eq!(joined.samples(), &[0.1, 0.2, 0.3]);
+        assert_eq!(joined.sample_rate(), 16000);
+
+        let c = AudioBuffer::from_samples(vec![0.4], 44100);
+        let err = AudioBuffer::concat(&[a, c]).unwrap_err();
+        assert!(err.to_string().contains("44100Hz"));
+
+        assert!(AudioBuffer::concat(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_concat_crossfade() {
+        let high = AudioBuffer::from_samples(vec![1.0; 100], 1000);
+        let low = AudioBuffer::from_samples(vec![-1.0; 100], 1000);
+        let joined = AudioBuffer::concat_crossfade(&[high.clone(), low], 10).unwrap();
+        assert_eq!(joined.len(), 190);
+        assert_eq!(joined.samples()[89], 1.0);
+        assert_eq!(joined.samples()[100], -1.0);
+
+        // The seam ramps down in small, even steps instead of jumping by 2.0
+        let seam = &joined.samples()[89..=100];
+        for pair in seam.windows(2) {
+            let step = pair[0] - pair[1];
+            assert!(step > 0.0 && step < 0.2, "step {}", step);
+        }
+
+        let other_rate = AudioBuffer::from_samples(vec![0.0; 10], 16000);
+        assert!(AudioBuffer::concat_crossfade(&[high, other_rate], 10).is_err());
+    }
+
+    fn tone(freq: f32, sample_rate: u32, seconds: f32) -> AudioBuffer {
+        let len = (sample_rate as f32 * seconds) as usize;
+        let samples = (0..len)
+            .map(|i| (i as f32 / sample_rate as f32 * freq * std::f32::consts::TAU).sin())
+            .collect();
+        AudioBuffer::from_samples(samples, sample_rate)
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_compress_dynamics() {
+        // A 0dBFS tone is 20dB over a -20dB threshold; 4:1 leaves 5dB of it
+        let mut loud = tone(440.0, 16000, 0.5);
+        loud.compress_dynamics(-20.0, 4.0);
+        let peak = loud.samples()[1600..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((20.0 * peak.log10() + 15.0).abs() < 0.1, "{}", peak);
+
+        // Audio under the threshold, and a 1:1 ratio, are untouched
+        let mut quiet = AudioBuffer::from_samples(vec![0.05, -0.05], 16000);
+        quiet.compress_dynamics(-20.0, 4.0);
+        assert_eq!(quiet.samples(), &[0.05, -0.05]);
+        let mut unity = tone(440.0, 16000, 0.1);
+        unity.compress_dynamics(-20.0, 1.0);
+        assert_eq!(unity.samples(), tone(440.0, 16000, 0.1).samples());
     }
 
-    Ok(buffer)
+    #[test]
+    fn test_normalize_rms() {
+        let mut buffer = AudioBuffer::from_samples(vec![0.01, -0.01, 0.01, -0.01], 16000);
+        assert!((buffer.normalize_rms(0.1, 100.0) - 10.0).abs() < 1e-4);
+        assert!((buffer.samples()[0] - 0.1).abs() < 1e-6);
+
+        // Gain is capped, and loud audio is attenuated
+        let mut quiet = AudioBuffer::from_samples(vec![0.001; 4], 16000);
+        assert_eq!(quiet.normalize_rms(0.1, 10.0), 10.0);
+        let mut loud = AudioBuffer::from_samples(vec![0.5, -0.5], 16000);
+        assert!((loud.normalize_rms(0.25, 10.0) - 0.5).abs() < 1e-6);
+
+        let mut silent = AudioBuffer::from_samples(vec![0.0; 4], 16000);
+        assert_eq!(silent.normalize_rms(0.1, 10.0), 1.0);
+    }
+
+    #[test]
+    fn test_energy_envelope() {
+        let mut buffer = AudioBuffer::from_samples(vec![0.5; 1600], 16000);
+        buffer.append(&[0.0; 800]);
+
+        // 20ms windows every 10ms over 150ms of audio
+        let envelope = buffer.energy_envelope(20, 10);
+        assert_eq!(envelope.len(), 15);
+        assert!((envelope[0] - 0.5).abs() < 1e-6);
+        assert!((envelope[9] - 0.5_f32 / 2.0_f32.sqrt()).abs() < 1e-6);
+        assert_eq!(envelope[14], 0.0);
+
+        assert!(buffer.energy_envelope(0, 10).is_empty());
+        assert!(AudioBuffer::new().energy_envelope(20, 10).is_empty());
+    }
+
+    #[test]
+    fn test_silence_regions() {
+        let mut buffer = AudioBuffer::from_samples(vec![0.0; 24000], 16000);
+        buffer.append(&[0.3; 8000]);
+        buffer.append(&[0.0; 4800]);
+
+        assert_eq!(buffer.silence_regions(1000, 0.5), vec![(0, 1500)]);
+        assert_eq!(buffer.silence_regions(200, 0.5), vec![(0, 1500), (2010, 2300)]);
+        assert!(AudioBuffer::new().silence_regions(0, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_split_on_silence() {
+        // Speech, 1.5s pause, speech, trailing silence
+        let mut buffer = AudioBuffer::from_samples(vec![0.3; 16000], 16000);
+        buffer.append(&[0.0; 24000]);
+        buffer.append(&[0.3; 16000]);
+        buffer.append(&[0.0; 24000]);
+
+        let chunks = buffer.split_on_silence(1000, 0.5);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].0, 0);
+        assert!((chunks[1].0 - 1750).abs() <= 30, "{}", chunks[1].0);
+        assert_eq!(chunks.iter().map(|(_, c)| c.len()).sum::<usize>(), buffer.len());
+
+        assert_eq!(buffer.split_on_silence(2000, 0.5).len(), 1);
+        assert!(AudioBuffer::new().split_on_silence(1000, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_estimate_snr_db() {
+        // One second of full-scale tone (RMS ~0.71) then one of noise (RMS ~0.0035)
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..16000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed as f32 / u32::MAX as f32 - 0.5) * 0.012
+            })
+            .collect();
+        let mut buffer = tone(440.0, 16000, 1.0);
+        buffer.append(&noise);
+        let snr = buffer.estimate_snr_db();
+        assert!((snr - 46.2).abs() < 1.0, "snr: {}", snr);
+
+        let mut clean = tone(440.0, 16000, 1.0);
+        clean.append(&[0.0; 16000]);
+        assert_eq!(clean.estimate_snr_db(), SNR_MAX_DB);
+
+        assert_eq!(AudioBuffer::from_samples(vec![0.0; 16000], 16000).estimate_snr_db(), SNR_SILENT_DB);
+        assert_eq!(AudioBuffer::new().estimate_snr_db(), SNR_SILENT_DB);
+    }
+
+    #[test]
+    fn test_speech_bandpass() {
+        let level_after = |freq: f32| {
+            let mut buffer = tone(freq, 48000, 1.0);
+            buffer.speech_bandpass();
+            // Skip the filter's settling time
+            rms(&buffer.samples()[24_000..]) / rms(&tone(freq, 48000, 1.0).samples()[24_000..])
+        };
+
+        assert!(level_after(30.0) < 0.05, "30Hz: {}", level_after(30.0));
+        assert!(level_after(12000.0) < 0.1, "12kHz: {}", level_after(12000.0));
+        assert!((level_after(1000.0) - 1.0).abs() < 0.02, "1kHz: {}", level_after(1000.0));
+    }
+
+    #[test]
+    fn test_resample_qualities_preserve_speech_band() {
+        let input = tone(440.0, 48000, 0.5);
+        for quality in [ResampleQuality::Linear, ResampleQuality::Cubic, ResampleQuality::SincHigh] {
+            let output = input.resample_with(16000, quality).unwrap();
+            assert_eq!(output.len(), 8000);
+            // Ignore the edges, where the sinc kernel is truncated
+            let level = rms(&output.samples()[500..7500]);
+            assert!((level - rms(input.samples())).abs() < 0.02, "{:?}: {}", quality, level);
+        }
+    }
+
+    #[test]
+    fn test_resample_preserves_duration() {
+        for (len, rate) in [(1000, 44100), (44_099, 44100), (7, 48000), (22_051, 22050), (1234, 8000)] {
+            let input = AudioBuffer::from_samples(vec![0.1; len], rate);
+            let output = input.resample(WHISPER_SAMPLE_RATE).unwrap();
+            let half_sample = 0.5 / WHISPER_SAMPLE_RATE as f32;
+            assert!(
+                (output.duration_seconds() - input.duration_seconds()).abs() <= half_sample,
+                "{} samples at {}Hz -> {}",
+                len,
+                rate,
+                output.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_resample_sinc_anti_aliasing() {
+        // 12kHz is above the 8kHz Nyquist of the target rate and must not alias
+        let input = tone(12000.0, 48000, 0.5);
+        let linear = input.resample_with(16000, ResampleQuality::Linear).unwrap();
+        let sinc = input.resample_with(16000, ResampleQuality::SincHigh).unwrap();
+        assert!(rms(&linear.samples()[500..7500]) > 0.3);
+        assert!(rms(&sinc.samples()[500..7500]) < 0.01);
+    }
+
+    #[test]
+    fn test_resample_polyphase_matches_sinc() {
+        for (rate, seconds) in [(48000, 2.0), (44100, 2.0), (22050, 1.0), (8000, 1.0)] {
+            let input = tone(3000.0, rate, seconds);
+            let ratio = WHISPER_SAMPLE_RATE as f64 / rate as f64;
+            let new_len = (input.len() as f64 * ratio).round() as usize;
+
+            let general = resample_sinc(input.samples(), ratio, new_len);
+            let fast = input.resample_with(WHISPER_SAMPLE_RATE, ResampleQuality::SincHigh).unwrap();
+
+            assert_eq!(fast.len(), general.len());
+            let max_diff = fast
+                .samples()
+                .iter()
+                .zip(&general)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f32::max);
+            assert!(max_diff < 1e-5, "{}Hz: {}", rate, max_diff);
+        }
+    }
+
+    /// Compares polyphase and general sinc resampling speed; run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "timing only, not a correctness check"]
+    fn bench_resample_polyphase() {
+        for rate in [48000, 44100, 22050, 8000] {
+            let input = tone(3000.0, rate, 10.0);
+            let ratio = WHISPER_SAMPLE_RATE as f64 / rate as f64;
+            let new_len = (input.len() as f64 * ratio).round() as usize;
+
+            let started = Instant::now();
+            resample_sinc(input.samples(), ratio, new_len);
+            let general_time = started.elapsed();
+            let started = Instant::now();
+            input.resample_with(WHISPER_SAMPLE_RATE, ResampleQuality::SincHigh).unwrap();
+            let fast_time = started.elapsed();
+            println!("{}Hz: polyphase {:?}, general sinc {:?}", rate, fast_time, general_time);
+        }
+    }
+
+    #[test]
+    fn test_decode_ogg_opus_mono() {
+        let data = encode_ogg_opus(1.0, opus::Channels::Mono);
+        let (buffer, metadata) = decode_ogg_opus(Cursor::new(data)).unwrap();
+        assert_eq!(buffer.sample_rate(), OPUS_SAMPLE_RATE);
+        assert_eq!(buffer.len(), OPUS_SAMPLE_RATE as usize);
+        assert_eq!((metadata.codec, metadata.bits_per_sample), (AudioCodec::Opus, None));
+        assert_eq!(metadata.duration_ms, 1000);
+    }
+
+    #[test]
+    fn test_decode_ogg_opus_stereo() {
+        let data = encode_ogg_opus(0.5, opus::Channels::Stereo);
+        let buffer = decode_ogg_opus(Cursor::new(data)).unwrap().0;
+        assert_eq!(buffer.len(), OPUS_SAMPLE_RATE as usize / 2);
+        assert!(buffer.samples().iter().any(|s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_decode_ogg_non_opus() {
+        let mut out = Vec::new();
+        let mut writer = ogg::PacketWriter::new(&mut out);
+        writer
+            .write_packet(b"\x01vorbis".to_vec(), 1, ogg::PacketWriteEndInfo::EndStream, 0)
+            .unwrap();
+        drop(writer);
+
+        let err = decode_ogg_opus(Cursor::new(out)).unwrap_err();
+        assert!(matches!(err, WhisperError::UnsupportedFormat(_)));
+    }
 }
@@ -1,14 +1,26 @@
 //! Configuration types for Whisper transcription.
 
+use crate::audio::ResampleQuality;
+use crate::error::{Result, WhisperError};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Environment variable that overrides the models directory.
+pub const MODELS_DIR_ENV: &str = "WHISPER_MODELS_DIR";
+
+/// Models directory chosen at runtime with [`set_models_dir`].
+static MODELS_DIR_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
 
 /// Whisper model size variants.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[repr(C)]
 pub enum ModelSize {
     /// Tiny model (~75MB) - Fastest, least accurate
     Tiny,
     /// Base model (~142MB) - Fast, good for simple tasks
+    #[default]
     Base,
     /// Small model (~466MB) - Balanced speed/accuracy
     Small,
@@ -22,6 +34,14 @@ pub enum ModelSize {
     LargeV3,
     /// Large-v3-turbo - Optimized for speed
     LargeV3Turbo,
+    /// English-only tiny model (~75MB)
+    TinyEn,
+    /// English-only base model (~142MB)
+    BaseEn,
+    /// English-only small model (~466MB)
+    SmallEn,
+    /// English-only medium model (~1.5GB)
+    MediumEn,
 }
 
 impl ModelSize {
@@ -36,32 +56,126 @@ impl ModelSize {
             ModelSize::LargeV2 => "ggml-large-v2.bin",
             ModelSize::LargeV3 => "ggml-large-v3.bin",
             ModelSize::LargeV3Turbo => "ggml-large-v3-turbo.bin",
+            ModelSize::TinyEn => "ggml-tiny.en.bin",
+            ModelSize::BaseEn => "ggml-base.en.bin",
+            ModelSize::SmallEn => "ggml-small.en.bin",
+            ModelSize::MediumEn => "ggml-medium.en.bin",
+        }
+    }
+
+    /// Identifies a model from its file name, e.g. "ggml-base.en.bin" or a
+    /// quantized "ggml-large-v3-turbo-q5_0.bin".
+    ///
+    /// Returns `None` for names that do not follow the ggml naming scheme.
+    pub fn from_filename(name: &str) -> Option<ModelSize> {
+        let stem = name.strip_prefix("ggml-")?.strip_suffix(".bin")?;
+        // Quantized models append their weight type after a dash
+        let stem = match stem.rsplit_once('-') {
+            Some((model, quant))
+                if quant.strip_prefix('q').is_some_and(|q| q.starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                model
+            }
+            _ => stem,
+        };
+        match stem {
+            "tiny" => Some(ModelSize::Tiny),
+            "base" => Some(ModelSize::Base),
+            "small" => Some(ModelSize::Small),
+            "medium" => Some(ModelSize::Medium),
+            "large" | "large-v1" => Some(ModelSize::Large),
+            "large-v2" => Some(ModelSize::LargeV2),
+            "large-v3" => Some(ModelSize::LargeV3),
+            "large-v3-turbo" => Some(ModelSize::LargeV3Turbo),
+            "tiny.en" => Some(ModelSize::TinyEn),
+            "base.en" => Some(ModelSize::BaseEn),
+            "small.en" => Some(ModelSize::SmallEn),
+            "medium.en" => Some(ModelSize::MediumEn),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the model supports languages other than English.
+    ///
+    /// English-only models can neither transcribe other languages nor translate.
+    pub fn is_multilingual(&self) -> bool {
+        !matches!(
+            self,
+            ModelSize::TinyEn | ModelSize::BaseEn | ModelSize::SmallEn | ModelSize::MediumEn
+        )
+    }
+
+    /// Returns the file name of the model quantized to `quantization` (e.g.
+    /// "q5_0" gives "ggml-large-v3-q5_0.bin"), or of the full model for `None`.
+    pub fn quantized_filename(&self, quantization: Option<&str>) -> String {
+        match quantization {
+            Some(quantization) => {
+                let stem = self.filename().trim_end_matches(".bin");
+                format!("{}-{}.bin", stem, quantization)
+            }
+            None => self.filename().to_string(),
         }
     }
 
-    /// Returns approximate model size in bytes.
+    /// Returns approximate size in bytes of the model quantized to
+    /// `quantization`, or `None` if the quantization is not one of
+    /// [`QUANTIZATIONS`].
+    pub fn quantized_size_bytes(&self, quantization: Option<&str>) -> Option<u64> {
+        let ratio = match quantization {
+            Some(quantization) => quantization_size_ratio(quantization)?,
+            None => 1.0,
+        };
+        Some((self.size_bytes() as f64 * ratio) as u64)
+    }
+
+    /// Returns approximate size in bytes of the full (f16) model.
     pub fn size_bytes(&self) -> u64 {
         match self {
-            ModelSize::Tiny => 75_000_000,
-            ModelSize::Base => 142_000_000,
-            ModelSize::Small => 466_000_000,
-            ModelSize::Medium => 1_500_000_000,
+            ModelSize::Tiny | ModelSize::TinyEn => 75_000_000,
+            ModelSize::Base | ModelSize::BaseEn => 142_000_000,
+            ModelSize::Small | ModelSize::SmallEn => 466_000_000,
+            ModelSize::Medium | ModelSize::MediumEn => 1_500_000_000,
             ModelSize::Large => 2_900_000_000,
             ModelSize::LargeV2 => 2_900_000_000,
             ModelSize::LargeV3 => 2_900_000_000,
             ModelSize::LargeV3Turbo => 1_600_000_000,
         }
     }
+
+    /// Returns a typical realtime factor (processing time / audio duration)
+    /// on Apple Silicon with Metal, used when no measurement is available.
+    pub fn benchmark_realtime_factor(&self) -> f64 {
+        match self {
+            ModelSize::Tiny | ModelSize::TinyEn => 0.02,
+            ModelSize::Base | ModelSize::BaseEn => 0.04,
+            ModelSize::Small | ModelSize::SmallEn => 0.10,
+            ModelSize::Medium | ModelSize::MediumEn => 0.25,
+            ModelSize::Large => 0.50,
+            ModelSize::LargeV2 => 0.50,
+            ModelSize::LargeV3 => 0.50,
+            ModelSize::LargeV3Turbo => 0.15,
+        }
+    }
 }
 
-impl Default for ModelSize {
-    fn default() -> Self {
-        ModelSize::Base
+/// Quantized weight formats whisper.cpp publishes models in.
+pub const QUANTIZATIONS: [&str; 5] = ["q4_0", "q4_1", "q5_0", "q5_1", "q8_0"];
+
+/// Size of a model quantized to `quantization`, relative to the f16 model.
+fn quantization_size_ratio(quantization: &str) -> Option<f64> {
+    match quantization {
+        "q4_0" => Some(0.30),
+        "q4_1" => Some(0.33),
+        "q5_0" => Some(0.36),
+        "q5_1" => Some(0.39),
+        "q8_0" => Some(0.55),
+        _ => None,
     }
 }
 
 /// Language configuration for transcription.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LanguageConfig {
     /// Source language code (e.g., "en", "fr", "auto").
     pub source: String,
@@ -79,32 +193,181 @@ impl Default for LanguageConfig {
 }
 
 /// Main configuration for Whisper transcription.
+///
+/// Missing fields take their default value when deserializing, so settings
+/// persisted by an older version keep loading as new fields are added.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WhisperConfig {
     /// Path to the model file.
     pub model_path: String,
     /// Model size (used if model_path not specified).
     pub model_size: ModelSize,
+    /// Quantized variant of `model_size` to load, e.g. "q5_0" for
+    /// `ggml-large-v3-q5_0.bin` (`None` = full model).
+    ///
+    /// Must be one of [`QUANTIZATIONS`]. Ignored when `model_path` is set.
+    pub quantization: Option<String>,
     /// Language configuration.
     pub language: LanguageConfig,
+    /// Languages auto-detection may choose from (empty = any language).
+    ///
+    /// Only used when the source language is "auto".
+    pub detection_languages: Vec<String>,
+    /// Model paths to use instead of the default model for specific
+    /// languages, keyed by language code (e.g. a fine-tuned "en" model).
+    ///
+    /// Applies when the language is set or detected; with "auto", the
+    /// language is detected before decoding. Models load on first use.
+    pub language_model_paths: HashMap<String, String>,
     /// Number of threads to use (0 = auto).
     pub n_threads: u32,
+    /// Number of processors to split one decode across (0 or 1 = single).
+    ///
+    /// Long audio is cut into equal parts of at least 30 seconds, decoded in
+    /// parallel, each with `n_threads` threads. Faster on machines with many
+    /// cores, but words at the cuts can be garbled or misplaced because each
+    /// part is decoded without the context of the one before it.
+    pub n_processors: u32,
     /// Enable GPU acceleration (Metal on macOS).
     pub use_gpu: bool,
     /// Enable flash attention.
     pub flash_attention: bool,
     /// Maximum audio duration to process (seconds).
     pub max_duration_seconds: u32,
+    /// Wall-clock budget for one transcription in milliseconds (0 = unlimited).
+    ///
+    /// Decoding stops once it is exceeded; the segments finished so far are
+    /// returned with `timed_out` set.
+    pub max_processing_ms: u64,
     /// Temperature for sampling (0.0 = greedy).
     pub temperature: f32,
-    /// Enable word-level timestamps.
+    /// Amount added to the temperature each time a decode is rejected by
+    /// `logprob_threshold` or `entropy_threshold` (0.0 = never retry).
+    pub temperature_increment: f32,
+    /// Beam width for beam search decoding (0 or 1 = greedy decoding).
+    pub beam_size: u32,
+    /// Beam search patience factor; higher values explore more candidates
+    /// before settling, trading speed for quality.
+    pub patience: f32,
+    /// Candidates sampled per decode with greedy decoding, keeping the most
+    /// probable (0 or 1 = one).
+    ///
+    /// Only used when sampling can happen: at a nonzero `temperature`, or
+    /// on `temperature_increment` retries.
+    pub best_of: u32,
+    /// Decode once more with greedy decoding when beam search returns no
+    /// segments from audio with speech-level energy, which happens on some
+    /// short utterances. The result then has `greedy_fallback` set.
+    pub greedy_fallback: bool,
+    /// Text given to the decoder as preceding context, e.g. names or
+    /// vocabulary the audio is likely to contain.
+    pub initial_prompt: Option<String>,
+    /// In chunked transcription, give each chunk the end of the previous
+    /// chunk's text as its prompt, so names and spelling stay consistent
+    /// across chunks. Ignored when `initial_prompt` is set.
+    pub carry_context: bool,
+    /// Decode each 30-second window with the text of the windows before it
+    /// as context (whisper's default).
+    ///
+    /// Keeps long transcripts coherent, but a hallucination in one window
+    /// tends to repeat in the following ones. Turning this off decodes every
+    /// window without any text context, which also drops `initial_prompt`
+    /// (whisper keeps it in the same context) and disables `carry_context`.
+    pub condition_on_previous_text: bool,
+    /// Enable word-level timestamps, filling each segment's `words`.
     pub word_timestamps: bool,
+    /// Audio shorter than this is not transcribed and yields an empty result.
+    ///
+    /// Whisper tends to hallucinate text (e.g. "Thank you.") on very short clips.
+    pub min_audio_ms: u32,
     /// Maximum segment length in characters.
     pub max_segment_length: u32,
+    /// Segments shorter than this many milliseconds are merged into a
+    /// neighbour (0 = keep every segment).
+    ///
+    /// Fast speech can decode into choppy fragments of a word or two.
+    pub min_segment_ms: i64,
     /// Enable VAD (Voice Activity Detection).
+    ///
+    /// Silences longer than about a second are cut before decoding. Off by
+    /// default, since audio that stays below `vad_threshold` throughout is
+    /// then returned empty without being decoded.
     pub vad_enabled: bool,
     /// VAD threshold (0.0 - 1.0).
     pub vad_threshold: f32,
+    /// With `vad_enabled`, report segment times against the original audio
+    /// rather than the trimmed audio that was decoded.
+    pub report_original_timestamps: bool,
+    /// Token ids the decoder may never emit (e.g. unwanted punctuation).
+    pub suppress_tokens: Vec<i32>,
+    /// Populate each segment's `tokens` with the raw decoder output.
+    pub include_tokens: bool,
+    /// Also collect the decoder output with special tokens (timestamps,
+    /// end-of-text, ...) into the result's `raw_text`, for debugging.
+    ///
+    /// Segment text and `text` stay free of special tokens either way.
+    pub keep_special_tokens: bool,
+    /// Store each segment's text exactly as whisper produced it, leading
+    /// space included, in the segment's `raw_text`, for alignment tooling
+    /// that must reconstruct the decoder output.
+    ///
+    /// Unaffected by `trim_segment_whitespace`.
+    pub keep_raw_segment_text: bool,
+    /// Segments with confidence below this value are dropped (0.0 = keep all).
+    pub min_confidence: f32,
+    /// Average log probability below which a decode is rejected and retried
+    /// at a higher temperature.
+    pub logprob_threshold: f32,
+    /// Token entropy below which a decode is rejected as repetitive and
+    /// retried at a higher temperature; low entropy means the decoder is
+    /// looping on the same tokens.
+    pub entropy_threshold: f32,
+    /// Band-limit input audio to the speech range before transcription,
+    /// removing rumble and hiss.
+    pub speech_bandpass: bool,
+    /// Boost quiet recordings to a typical speech level before transcription.
+    ///
+    /// Gain is capped at +20dB so noise floors are not blown up, and loud
+    /// audio is left as is.
+    pub auto_gain: bool,
+    /// Compress the dynamic range before transcription, evening out
+    /// recordings that mix whispered and shouted speech.
+    ///
+    /// Peaks above -20dBFS are reduced 4:1; runs before `auto_gain`.
+    pub compress_dynamics: bool,
+    /// Resampling algorithm used when input audio is not 16kHz.
+    pub resample_quality: ResampleQuality,
+    /// Maximum characters of text returned across the FFI boundary (0 = unlimited).
+    ///
+    /// Longer text is cut at this length and suffixed with `…`, and the C
+    /// result's `text_truncated` flag is set. Guards the host app against
+    /// runaway decodes (e.g. a hallucination loop) producing huge strings.
+    pub max_result_chars: usize,
+    /// Maximum segments kept per transcription (0 = unlimited).
+    ///
+    /// Later segments are dropped and the result's `segments_truncated` flag
+    /// is set. Guards against pathological decodes producing thousands of
+    /// tiny segments.
+    pub max_segments: usize,
+    /// Let whisper.cpp print its progress and realtime output to stderr,
+    /// for diagnosing unexpected results.
+    pub verbose: bool,
+    /// Trim Whisper's leading/trailing spaces from each segment's text.
+    ///
+    /// The full transcript is joined as `join_with_spaces` decides either way.
+    pub trim_segment_whitespace: bool,
+    /// Whether the full transcript joins segments with spaces.
+    ///
+    /// `None` decides from the language: scripts without word spaces, like
+    /// Japanese, Chinese and Thai, are joined directly.
+    pub join_with_spaces: Option<bool>,
+    /// Drop segments with no letters or digits, such as a lone "." or "。".
+    pub drop_punctuation_segments: bool,
+    /// Adjust segment boundaries so timestamps never go backwards or overlap.
+    ///
+    /// Overlapping cues break some subtitle players.
+    pub fix_overlaps: bool,
 }
 
 impl Default for WhisperConfig {
@@ -112,16 +375,50 @@ impl Default for WhisperConfig {
         Self {
             model_path: String::new(),
             model_size: ModelSize::Base,
+            quantization: None,
             language: LanguageConfig::default(),
+            detection_languages: Vec::new(),
+            language_model_paths: HashMap::new(),
             n_threads: 0, // Auto-detect
+            n_processors: 1,
             use_gpu: true,
             flash_attention: true,
             max_duration_seconds: 300, // 5 minutes
+            max_processing_ms: 0,
             temperature: 0.0,
+            temperature_increment: 0.2,
+            beam_size: 1,
+            patience: 1.0,
+            best_of: 1,
+            greedy_fallback: true,
+            initial_prompt: None,
+            carry_context: true,
+            condition_on_previous_text: true,
             word_timestamps: false,
+            min_audio_ms: 100,
             max_segment_length: 0, // No limit
-            vad_enabled: true,
+            min_segment_ms: 0,
+            vad_enabled: false,
             vad_threshold: 0.5,
+            report_original_timestamps: true,
+            suppress_tokens: Vec::new(),
+            include_tokens: false,
+            keep_special_tokens: false,
+            keep_raw_segment_text: false,
+            min_confidence: 0.0,
+            logprob_threshold: -1.0,
+            entropy_threshold: 2.4,
+            speech_bandpass: false,
+            auto_gain: false,
+            compress_dynamics: false,
+            resample_quality: ResampleQuality::default(),
+            max_result_chars: 1_000_000,
+            max_segments: 0,
+            trim_segment_whitespace: false,
+            join_with_spaces: None,
+            drop_punctuation_segments: false,
+            fix_overlaps: true,
+            verbose: false,
         }
     }
 }
@@ -143,6 +440,12 @@ impl WhisperConfig {
         }
     }
 
+    /// Sets the quantized variant of the model size to load.
+    pub fn quantization(mut self, quantization: impl Into<String>) -> Self {
+        self.quantization = Some(quantization.into());
+        self
+    }
+
     /// Sets the source language.
     pub fn language(mut self, lang: impl Into<String>) -> Self {
         self.language.source = lang.into();
@@ -166,4 +469,373 @@ impl WhisperConfig {
         self.use_gpu = enabled;
         self
     }
+
+    /// Sets the beam width (0 or 1 = greedy decoding).
+    pub fn beam_size(mut self, n: u32) -> Self {
+        self.beam_size = n;
+        self
+    }
+
+    /// Sets the number of candidates sampled with greedy decoding.
+    pub fn best_of(mut self, n: u32) -> Self {
+        self.best_of = n;
+        self
+    }
+
+    /// Sets the text given to the decoder as preceding context.
+    pub fn initial_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.initial_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Sets the beam search patience factor.
+    pub fn patience(mut self, patience: f32) -> Self {
+        self.patience = patience;
+        self
+    }
+
+    /// Parses a config from JSON; omitted fields use their defaults.
+    pub fn from_json(s: &str) -> Result<Self> {
+        serde_json::from_str(s)
+            .map_err(|e| WhisperError::ConfigError(format!("Invalid config JSON: {}", e)))
+    }
+
+    /// Serializes the config to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("WhisperConfig is always serializable")
+    }
+
+    /// Returns the configured decoding options, as used by
+    /// [`TranscriptionEngine::transcribe`](crate::TranscriptionEngine::transcribe).
+    pub fn transcription_options(&self) -> TranscriptionOptions {
+        TranscriptionOptions {
+            language: self.language.clone(),
+            initial_prompt: self.initial_prompt.clone(),
+            temperature: self.temperature,
+            temperature_increment: self.temperature_increment,
+            beam_size: self.beam_size,
+            patience: self.patience,
+            best_of: self.best_of,
+            greedy_fallback: self.greedy_fallback,
+            condition_on_previous_text: self.condition_on_previous_text,
+            word_timestamps: self.word_timestamps,
+        }
+    }
+}
+
+/// How to decode one transcription, separate from the model and audio
+/// settings in [`WhisperConfig`].
+///
+/// Passed to [`TranscriptionEngine::transcribe_with`](crate::TranscriptionEngine::transcribe_with)
+/// to vary decoding between calls without re-initializing the engine. Each
+/// field means the same as its namesake in `WhisperConfig`, and the defaults
+/// match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionOptions {
+    /// Source language and translation.
+    pub language: LanguageConfig,
+    /// Text given to the decoder as preceding context.
+    pub initial_prompt: Option<String>,
+    /// Temperature for sampling (0.0 = greedy).
+    pub temperature: f32,
+    /// Amount added to the temperature on each rejected decode.
+    pub temperature_increment: f32,
+    /// Beam width for beam search decoding (0 or 1 = greedy decoding).
+    pub beam_size: u32,
+    /// Beam search patience factor.
+    pub patience: f32,
+    /// Candidates sampled per decode with greedy decoding.
+    pub best_of: u32,
+    /// Retry with greedy decoding when beam search decodes nothing.
+    pub greedy_fallback: bool,
+    /// Decode each window with the text of the windows before it as context.
+    pub condition_on_previous_text: bool,
+    /// Enable word-level timestamps.
+    pub word_timestamps: bool,
+}
+
+impl Default for TranscriptionOptions {
+    fn default() -> Self {
+        WhisperConfig::default().transcription_options()
+    }
+}
+
+impl TranscriptionOptions {
+    /// Sets the source language.
+    pub fn language(mut self, lang: impl Into<String>) -> Self {
+        self.language.source = lang.into();
+        self
+    }
+
+    /// Enables translation to English.
+    pub fn translate(mut self) -> Self {
+        self.language.translate_to_english = true;
+        self
+    }
+
+    /// Sets the text given to the decoder as preceding context.
+    pub fn initial_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.initial_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Sets the sampling temperature.
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sets the beam width (0 or 1 = greedy decoding).
+    pub fn beam_size(mut self, n: u32) -> Self {
+        self.beam_size = n;
+        self
+    }
+
+    /// Sets the beam search patience factor.
+    pub fn patience(mut self, patience: f32) -> Self {
+        self.patience = patience;
+        self
+    }
+
+    /// Sets the number of candidates sampled with greedy decoding.
+    pub fn best_of(mut self, n: u32) -> Self {
+        self.best_of = n;
+        self
+    }
+
+    /// Enables or disables word-level timestamps.
+    pub fn word_timestamps(mut self, enabled: bool) -> Self {
+        self.word_timestamps = enabled;
+        self
+    }
+}
+
+/// Returns the directory where models are looked up by default.
+///
+/// Resolution order:
+/// 1. The directory passed to [`set_models_dir`]
+/// 2. `$WHISPER_MODELS_DIR`
+/// 3. On macOS, `~/Library/Application Support/BetterFasterWhisper/Models`
+/// 4. Elsewhere, `$XDG_DATA_HOME/BetterFasterWhisper/models`, falling back to
+///    `~/.local/share/BetterFasterWhisper/models`
+pub fn default_models_dir() -> Result<PathBuf> {
+    let dir_override = MODELS_DIR_OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+    models_dir(dir_override.as_deref(), |key| std::env::var(key).ok())
+}
+
+/// Picks the models directory from an override, else the environment.
+fn models_dir(dir_override: Option<&Path>, env: impl Fn(&str) -> Option<String>) -> Result<PathBuf> {
+    if let Some(dir) = dir_override {
+        return Ok(dir.to_path_buf());
+    }
+
+    resolve_models_dir(env).ok_or_else(|| {
+        WhisperError::ConfigError(format!(
+            "Cannot determine models directory: set {} or HOME",
+            MODELS_DIR_ENV
+        ))
+    })
+}
+
+/// Overrides the models directory for the whole process, or restores the
+/// default lookup when `dir` is `None`.
+///
+/// Fails with `ConfigError`, keeping the current directory, if `dir` is not
+/// an existing directory.
+pub fn set_models_dir(dir: Option<&Path>) -> Result<()> {
+    check_models_dir(dir)?;
+    *MODELS_DIR_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = dir.map(Path::to_path_buf);
+    Ok(())
+}
+
+/// Checks that a models directory override, if any, is an existing directory.
+fn check_models_dir(dir: Option<&Path>) -> Result<()> {
+    match dir.filter(|dir| !dir.is_dir()) {
+        Some(dir) => Err(WhisperError::ConfigError(format!(
+            "Models directory does not exist: {}",
+            dir.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns where the model file for `size` is expected in the models directory.
+pub fn default_model_path(size: ModelSize) -> Result<PathBuf> {
+    quantized_model_path(size, None)
+}
+
+/// Returns where the model file for `size` quantized to `quantization` is
+/// expected in the models directory.
+///
+/// Fails with `ConfigError` if `quantization` is not one of [`QUANTIZATIONS`].
+pub fn quantized_model_path(size: ModelSize, quantization: Option<&str>) -> Result<PathBuf> {
+    if let Some(quantization) = quantization.filter(|q| !QUANTIZATIONS.contains(q)) {
+        return Err(WhisperError::ConfigError(format!(
+            "Unknown quantization '{}' (expected one of {})",
+            quantization,
+            QUANTIZATIONS.join(", ")
+        )));
+    }
+    Ok(default_models_dir()?.join(size.quantized_filename(quantization)))
+}
+
+/// Resolves the models directory using the given environment lookup.
+fn resolve_models_dir(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let env = |key: &str| env(key).filter(|v| !v.is_empty());
+
+    if let Some(dir) = env(MODELS_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+
+    if cfg!(target_os = "macos") {
+        return env("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library/Application Support")
+                .join("BetterFasterWhisper/Models")
+        });
+    }
+
+    env("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|data| data.join("BetterFasterWhisper/models"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_model_size_from_filename() {
+        assert_eq!(ModelSize::from_filename("ggml-base.bin"), Some(ModelSize::Base));
+        assert_eq!(ModelSize::from_filename("ggml-medium.en.bin"), Some(ModelSize::MediumEn));
+        assert_eq!(ModelSize::from_filename("ggml-large-v3-turbo.bin"), Some(ModelSize::LargeV3Turbo));
+        assert_eq!(ModelSize::from_filename("ggml-small.en-q5_1.bin"), Some(ModelSize::SmallEn));
+        assert_eq!(ModelSize::from_filename("ggml-large-v3-turbo-q8_0.bin"), Some(ModelSize::LargeV3Turbo));
+        assert_eq!(ModelSize::from_filename("ggml-large-v2-q5_0.bin"), Some(ModelSize::LargeV2));
+        assert_eq!(ModelSize::from_filename("ggml-huge.bin"), None);
+        assert_eq!(ModelSize::from_filename("base.bin"), None);
+        assert_eq!(ModelSize::from_filename("ggml-base.gguf"), None);
+
+        for size in [ModelSize::Tiny, ModelSize::LargeV2, ModelSize::BaseEn] {
+            assert_eq!(ModelSize::from_filename(size.filename()), Some(size));
+        }
+    }
+
+    #[test]
+    fn test_quantized_model_naming() {
+        let size = ModelSize::LargeV3;
+        assert_eq!(size.quantized_filename(Some("q5_0")), "ggml-large-v3-q5_0.bin");
+        assert_eq!(ModelSize::BaseEn.quantized_filename(Some("q8_0")), "ggml-base.en-q8_0.bin");
+        assert_eq!(size.quantized_filename(None), size.filename());
+        assert_eq!(ModelSize::from_filename(&size.quantized_filename(Some("q4_1"))), Some(size));
+
+        assert_eq!(size.quantized_size_bytes(None), Some(size.size_bytes()));
+        assert!(size.quantized_size_bytes(Some("q5_0")).unwrap() < size.size_bytes() / 2);
+        assert_eq!(size.quantized_size_bytes(Some("q3_x")), None);
+        assert!(matches!(
+            quantized_model_path(size, Some("q3_x")),
+            Err(WhisperError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_transcription_options() {
+        let config = WhisperConfig::default().language("fr").beam_size(5).initial_prompt("Zeffut");
+        let options = config.transcription_options();
+        assert_eq!(options.language.source, "fr");
+        assert_eq!(options.beam_size, 5);
+        assert_eq!(options.initial_prompt.as_deref(), Some("Zeffut"));
+
+        let options = TranscriptionOptions::default().language("de").word_timestamps(true);
+        assert_eq!(options.language.source, "de");
+        assert!(options.word_timestamps);
+        assert_eq!(options.temperature_increment, WhisperConfig::default().temperature_increment);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let config = WhisperConfig::with_model_size(ModelSize::Small)
+            .language("fr")
+            .threads(4);
+        let parsed = WhisperConfig::from_json(&config.to_json()).unwrap();
+        assert_eq!(parsed.model_size, ModelSize::Small);
+        assert_eq!(parsed.language.source, "fr");
+        assert_eq!(parsed.n_threads, 4);
+    }
+
+    #[test]
+    fn test_json_partial_and_invalid() {
+        let parsed =
+            WhisperConfig::from_json(r#"{"model_path": "/m.bin", "language": {"translate_to_english": true}}"#)
+                .unwrap();
+        assert_eq!(parsed.model_path, "/m.bin");
+        assert_eq!(parsed.language.source, "auto");
+        assert!(parsed.language.translate_to_english);
+        assert_eq!(parsed.max_result_chars, WhisperConfig::default().max_result_chars);
+        // Silence trimming is opt-in
+        assert!(!parsed.vad_enabled);
+
+        let err = WhisperConfig::from_json("{\"n_threads\": -1}").unwrap_err();
+        assert!(matches!(err, WhisperError::ConfigError(_)));
+    }
+
+    #[test]
+    fn test_models_dir_env_override() {
+        let dir = resolve_models_dir(lookup(&[(MODELS_DIR_ENV, "/models"), ("HOME", "/home/u")]));
+        assert_eq!(dir, Some(PathBuf::from("/models")));
+    }
+
+    #[test]
+    fn test_models_dir_unresolvable() {
+        assert_eq!(resolve_models_dir(lookup(&[])), None);
+        assert_eq!(resolve_models_dir(lookup(&[(MODELS_DIR_ENV, "")])), None);
+    }
+
+    #[test]
+    fn test_models_dir_override() {
+        let missing = Path::new("/nonexistent/models");
+        assert!(matches!(check_models_dir(Some(missing)), Err(WhisperError::ConfigError(_))));
+        assert!(check_models_dir(None).is_ok());
+
+        let dir = std::env::temp_dir();
+        assert!(check_models_dir(Some(&dir)).is_ok());
+        let env = lookup(&[(MODELS_DIR_ENV, "/models")]);
+        assert_eq!(models_dir(Some(&dir), &env).unwrap(), dir);
+        assert_eq!(models_dir(None, &env).unwrap(), PathBuf::from("/models"));
+        assert!(matches!(models_dir(None, lookup(&[])), Err(WhisperError::ConfigError(_))));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_models_dir_macos() {
+        let dir = resolve_models_dir(lookup(&[("XDG_DATA_HOME", "/data"), ("HOME", "/Users/u")]));
+        assert_eq!(
+            dir,
+            Some(PathBuf::from("/Users/u/Library/Application Support/BetterFasterWhisper/Models"))
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_models_dir_xdg() {
+        let dir = resolve_models_dir(lookup(&[("XDG_DATA_HOME", "/data"), ("HOME", "/home/u")]));
+        assert_eq!(dir, Some(PathBuf::from("/data/BetterFasterWhisper/models")));
+
+        let dir = resolve_models_dir(lookup(&[("HOME", "/home/u")]));
+        assert_eq!(
+            dir,
+            Some(PathBuf::from("/home/u/.local/share/BetterFasterWhisper/models"))
+        );
+    }
 }
@@ -3,17 +3,35 @@
 //! This module provides C-compatible functions that can be called from Swift.
 //! All functions use C types and conventions for maximum compatibility.
 
-use crate::audio::AudioBuffer;
+use crate::audio::{validate_sample_rate, AudioBuffer};
 use crate::config::{ModelSize, WhisperConfig};
-use crate::transcription::TranscriptionEngine;
+use crate::error::WhisperError;
+use crate::streaming::{StreamingConfig, StreamingSession};
+use crate::transcription::{TranscriptionEngine, TranscriptionResult};
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
 
 // Global engine instance for FFI
 static ENGINE: Mutex<Option<TranscriptionEngine>> = Mutex::new(None);
 
+// Live session started by `whisper_stream_start`, with its own engine
+static STREAM: Mutex<Option<FfiStream>> = Mutex::new(None);
+
+/// Pushed audio that triggers a new partial result, in milliseconds.
+const STREAM_PARTIAL_INTERVAL_MS: u64 = 500;
+
+thread_local! {
+    /// Message of the most recent FFI failure on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
 /// Result codes for FFI functions.
 #[repr(C)]
 pub enum WhisperResultCode {
@@ -29,6 +47,23 @@ pub enum WhisperResultCode {
     InvalidParameter = -4,
     /// Transcription failed.
     TranscriptionFailed = -5,
+    /// The engine is in use by another call.
+    Busy = -6,
+    /// A caller-provided buffer is too small; the required size was reported.
+    BufferTooSmall = -7,
+}
+
+/// Sample formats accepted by the audio-ingest functions.
+///
+/// Passed across the boundary as an `i32` code so unknown values from C can
+/// be rejected instead of being undefined behavior.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhisperAudioFormat {
+    /// 32-bit float samples in [-1.0, 1.0].
+    F32 = 0,
+    /// Signed 16-bit PCM samples.
+    I16 = 1,
 }
 
 /// C-compatible transcription result.
@@ -42,8 +77,29 @@ pub struct CTranscriptionResult {
     pub segment_count: i32,
     /// Processing time in milliseconds.
     pub processing_time_ms: u64,
+    /// Part of `processing_time_ms` spent resampling.
+    pub resample_time_ms: u64,
+    /// Part of `processing_time_ms` spent decoding.
+    pub inference_time_ms: u64,
     /// Audio duration in milliseconds.
     pub audio_duration_ms: u64,
+    /// Duration of the audio actually decoded after VAD trimming, in milliseconds.
+    pub processed_duration_ms: u64,
+    /// Whether `text` was cut at the configured `max_result_chars` limit.
+    pub text_truncated: bool,
+    /// Whether segments were dropped at the configured `max_segments` limit.
+    pub segments_truncated: bool,
+    /// Whether the audio had speech-level energy; set with empty `text`, the
+    /// model produced nothing for audible input.
+    pub had_speech: bool,
+    /// Whether decoding stopped at the configured `max_processing_ms` budget,
+    /// leaving only the segments finished in time.
+    pub timed_out: bool,
+    /// Whether beam search decoded nothing and the text comes from the
+    /// configured `greedy_fallback` retry.
+    pub greedy_fallback: bool,
+    /// Segment confidence averaged weighted by duration (0.0 without segments).
+    pub average_confidence: f32,
     /// Result code.
     pub result_code: WhisperResultCode,
     /// Error message if result_code != Success.
@@ -57,13 +113,35 @@ impl Default for CTranscriptionResult {
             language: ptr::null_mut(),
             segment_count: 0,
             processing_time_ms: 0,
+            resample_time_ms: 0,
+            inference_time_ms: 0,
             audio_duration_ms: 0,
+            processed_duration_ms: 0,
+            text_truncated: false,
+            segments_truncated: false,
+            had_speech: false,
+            timed_out: false,
+            greedy_fallback: false,
+            average_confidence: 0.0,
             result_code: WhisperResultCode::Success,
             error_message: ptr::null_mut(),
         }
     }
 }
 
+impl CTranscriptionResult {
+    /// Creates a result carrying only an error code and message, which also
+    /// becomes the thread's last error.
+    fn failure(result_code: WhisperResultCode, message: &str) -> Self {
+        set_last_error(message);
+        Self {
+            result_code,
+            error_message: string_to_c_char(message),
+            ..Default::default()
+        }
+    }
+}
+
 /// C-compatible configuration.
 #[repr(C)]
 pub struct CWhisperConfig {
@@ -81,255 +159,1497 @@ pub struct CWhisperConfig {
     pub use_gpu: bool,
 }
 
+/// C-compatible model file metadata, filled by `whisper_inspect_model`.
+#[repr(C)]
+pub struct CModelMetadata {
+    /// Model size enum value, or -1 if the architecture is not recognized.
+    pub model_size: i32,
+    /// Whether the model supports languages other than English.
+    pub multilingual: bool,
+    /// Number of vocabulary tokens.
+    pub n_vocab: i32,
+    /// Number of audio encoder layers.
+    pub n_audio_layer: i32,
+    /// Number of text decoder layers.
+    pub n_text_layer: i32,
+    /// Number of mel frequency bins.
+    pub n_mels: i32,
+    /// Raw ggml weight type.
+    pub ftype: i32,
+    /// Weight format name (null-terminated), e.g. "f16" or "q5_0".
+    pub quantization: [c_char; 16],
+}
+
+/// C-compatible WAV properties, filled by `whisper_probe_wav`.
+#[repr(C)]
+pub struct CAudioInfo {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Number of interleaved channels.
+    pub channels: u16,
+    /// Bits per sample.
+    pub bits_per_sample: u16,
+    /// Duration in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// C-compatible segment of the last transcription, filled by `whisper_get_segment`.
+#[repr(C)]
+pub struct CSegment {
+    /// Start time in milliseconds.
+    pub start_ms: i64,
+    /// End time in milliseconds.
+    pub end_ms: i64,
+    /// Segment text (null-terminated UTF-8); free with `whisper_free_string`.
+    pub text: *mut c_char,
+    /// Mean token probability (0.0 - 1.0).
+    pub confidence: f32,
+    /// Speaker or channel index, or -1 if unknown.
+    pub speaker_id: i32,
+}
+
+/// C-compatible word of a segment, filled by `whisper_get_segment_words`.
+#[repr(C)]
+pub struct CWord {
+    /// Start time in milliseconds.
+    pub start_ms: i64,
+    /// End time in milliseconds.
+    pub end_ms: i64,
+    /// Word text (null-terminated UTF-8); free with `whisper_free_string`.
+    pub text: *mut c_char,
+    /// Mean token probability (0.0 - 1.0).
+    pub probability: f32,
+}
+
 // ============================================================================
 // FFI Functions
 // ============================================================================
 
+/// Opaque handle to an independently owned transcription engine.
+///
+/// Created with `whisper_create` and released with `whisper_destroy`. Unlike the
+/// global API, any number of handles can be alive at once.
+pub struct WhisperHandle {
+    engine: TranscriptionEngine,
+}
+
+/// Receives each result of a streaming session, with the user data given
+/// to `whisper_stream_start`.
+type PartialCallback = extern "C" fn(*const CTranscriptionResult, *mut c_void);
+
+/// A streaming session and where its results go.
+struct FfiStream {
+    session: StreamingSession,
+    on_partial: PartialCallback,
+    user_data: *mut c_void,
+    max_result_chars: usize,
+    /// Samples pushed since the last partial result.
+    pending_samples: u64,
+}
+
+// The caller guarantees `user_data` may be used from whichever thread pushes audio
+unsafe impl Send for FfiStream {}
+
+impl FfiStream {
+    /// Passes `result` to the callback as a C result, freed once it returns.
+    fn emit(&self, result: TranscriptionResult) {
+        let mut result = to_c_result(Ok(result), self.max_result_chars);
+        (self.on_partial)(&result, self.user_data);
+        unsafe { whisper_free_result(&mut result) };
+    }
+}
+
 /// Initializes the Whisper engine with the given configuration.
 ///
 /// # Safety
 /// The `config` pointer must be valid and properly initialized.
 #[no_mangle]
 pub unsafe extern "C" fn whisper_init(config: *const CWhisperConfig) -> WhisperResultCode {
-    if config.is_null() {
-        return WhisperResultCode::InvalidParameter;
-    }
+    ffi_guard(|| {
+        if config.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Config is null");
+        }
 
-    let c_config = &*config;
+        let engine = match config_from_c(&*config).and_then(create_engine) {
+            Ok(engine) => engine,
+            Err(code) => return code,
+        };
 
-    // Convert C config to Rust config
-    let model_path = if c_config.model_path.is_null() {
-        String::new()
-    } else {
-        match CStr::from_ptr(c_config.model_path).to_str() {
-            Ok(s) => s.to_string(),
-            Err(_) => return WhisperResultCode::InvalidParameter,
-        }
-    };
+        set_global_engine(engine);
+        WhisperResultCode::Success
+    })
+}
 
-    let language = if c_config.language.is_null() {
-        "auto".to_string()
-    } else {
-        match CStr::from_ptr(c_config.language).to_str() {
-            Ok(s) => s.to_string(),
-            Err(_) => "auto".to_string(),
+/// Initializes the Whisper engine with default configuration.
+#[no_mangle]
+pub extern "C" fn whisper_init_default() -> WhisperResultCode {
+    ffi_guard(|| {
+        let engine = match create_engine(WhisperConfig::default()) {
+            Ok(engine) => engine,
+            Err(code) => return code,
+        };
+
+        set_global_engine(engine);
+        WhisperResultCode::Success
+    })
+}
+
+/// Initializes the Whisper engine from a JSON-serialized `WhisperConfig`.
+///
+/// Fields missing from the JSON take their default values, so new config
+/// options never require changes to the C ABI.
+///
+/// # Safety
+/// The `json` pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_init_from_json(json: *const c_char) -> WhisperResultCode {
+    ffi_guard(|| {
+        if json.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Config JSON is null");
         }
-    };
 
-    let model_size = match c_config.model_size {
-        0 => ModelSize::Tiny,
-        1 => ModelSize::Base,
-        2 => ModelSize::Small,
-        3 => ModelSize::Medium,
-        4 => ModelSize::Large,
-        5 => ModelSize::LargeV2,
-        6 => ModelSize::LargeV3,
-        7 => ModelSize::LargeV3Turbo,
-        _ => ModelSize::Base,
-    };
+        let config = match CStr::from_ptr(json).to_str().map(WhisperConfig::from_json) {
+            Ok(Ok(config)) => config,
+            Ok(Err(e)) => return fail(WhisperResultCode::InvalidParameter, &e.to_string()),
+            Err(_) => return fail(WhisperResultCode::InvalidParameter, "Config JSON is not valid UTF-8"),
+        };
 
-    let rust_config = WhisperConfig {
-        model_path,
-        model_size,
-        language: crate::config::LanguageConfig {
-            source: language,
-            translate_to_english: c_config.translate,
-        },
-        n_threads: c_config.n_threads,
-        use_gpu: c_config.use_gpu,
-        ..Default::default()
-    };
+        let engine = match create_engine(config) {
+            Ok(engine) => engine,
+            Err(code) => return code,
+        };
 
-    let mut engine = TranscriptionEngine::new(rust_config);
+        set_global_engine(engine);
+        WhisperResultCode::Success
+    })
+}
 
-    match engine.initialize() {
-        Ok(()) => {
-            let mut global_engine = ENGINE.lock().unwrap();
-            *global_engine = Some(engine);
-            WhisperResultCode::Success
+/// Initializes the Whisper engine like `whisper_init`, also returning the
+/// error message on failure.
+///
+/// On failure `*out_error` receives an owned copy of the message, to be
+/// released with `whisper_free_string`; on success it is set to null.
+///
+/// # Safety
+/// - The `config` pointer must be valid and properly initialized.
+/// - `out_error` must be null or point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_init_checked(
+    config: *const CWhisperConfig,
+    out_error: *mut *mut c_char,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        let code = whisper_init(config);
+        if !out_error.is_null() {
+            *out_error = match code {
+                WhisperResultCode::Success => ptr::null_mut(),
+                _ => LAST_ERROR.with(|last| {
+                    last.borrow()
+                        .as_ref()
+                        .map_or(ptr::null_mut(), |message| message.clone().into_raw())
+                }),
+            };
         }
-        Err(crate::error::WhisperError::ModelNotFound(_)) => WhisperResultCode::ModelNotFound,
-        Err(_) => WhisperResultCode::Error,
-    }
+        code
+    })
 }
 
-/// Initializes the Whisper engine with default configuration.
+/// Frees a string returned by the library, such as from `whisper_init_checked`.
+///
+/// # Safety
+/// `s` must be null or a string returned by this library and not yet freed.
 #[no_mangle]
-pub extern "C" fn whisper_init_default() -> WhisperResultCode {
-    let mut engine = TranscriptionEngine::with_defaults();
+pub unsafe extern "C" fn whisper_free_string(s: *mut c_char) {
+    ffi_guard(|| {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
 
-    match engine.initialize() {
-        Ok(()) => {
-            let mut global_engine = ENGINE.lock().unwrap();
-            *global_engine = Some(engine);
-            WhisperResultCode::Success
+/// Creates an independent engine with the given configuration.
+///
+/// Returns null if the configuration is invalid or the model fails to load;
+/// `whisper_last_error` then describes why.
+///
+/// # Safety
+/// - The `config` pointer must be valid and properly initialized.
+/// - The returned handle must be released with `whisper_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_create(config: *const CWhisperConfig) -> *mut WhisperHandle {
+    ffi_guard(|| {
+        if config.is_null() {
+            set_last_error("Config is null");
+            return ptr::null_mut();
         }
-        Err(crate::error::WhisperError::ModelNotFound(_)) => WhisperResultCode::ModelNotFound,
-        Err(_) => WhisperResultCode::Error,
-    }
+
+        match config_from_c(&*config).and_then(create_engine) {
+            Ok(engine) => Box::into_raw(Box::new(WhisperHandle { engine })),
+            Err(_) => ptr::null_mut(),
+        }
+    })
 }
 
-/// Transcribes audio samples.
+/// Transcribes interleaved audio samples with the engine behind `handle`.
+///
+/// Channels are averaged to mono before transcription. A handle may be used
+/// from several threads at once, but must not be destroyed while a
+/// transcription on it is in progress.
 ///
 /// # Safety
-/// - `samples` must be a valid pointer to `sample_count` f32 values.
+/// - `handle` must have been returned by `whisper_create` and not yet destroyed.
+/// - `samples` must be a valid pointer to `sample_count` f32 values
+///   (`sample_count` counts samples across all channels).
 /// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
 #[no_mangle]
-pub unsafe extern "C" fn whisper_transcribe(
+pub unsafe extern "C" fn whisper_transcribe_handle(
+    handle: *const WhisperHandle,
     samples: *const f32,
     sample_count: usize,
     sample_rate: u32,
+    channels: u16,
 ) -> CTranscriptionResult {
-    let mut result = CTranscriptionResult::default();
+    whisper_transcribe_handle_samples(
+        handle,
+        samples.cast(),
+        sample_count,
+        WhisperAudioFormat::F32 as i32,
+        sample_rate,
+        channels,
+    )
+}
 
-    if samples.is_null() || sample_count == 0 {
-        result.result_code = WhisperResultCode::InvalidParameter;
-        result.error_message = string_to_c_char("Invalid audio samples");
-        return result;
-    }
+/// Transcribes interleaved samples in any `WhisperAudioFormat` with the
+/// engine behind `handle`.
+///
+/// # Safety
+/// - `handle` must have been returned by `whisper_create` and not yet destroyed.
+/// - `samples` must be a valid pointer to `sample_count` values of the type
+///   named by `format` (`sample_count` counts samples across all channels).
+/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe_handle_samples(
+    handle: *const WhisperHandle,
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    channels: u16,
+) -> CTranscriptionResult {
+    ffi_guard(|| {
+        if handle.is_null() {
+            return CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, "Handle is null");
+        }
 
-    let engine_guard = ENGINE.lock().unwrap();
-    let engine = match engine_guard.as_ref() {
-        Some(e) => e,
-        None => {
-            result.result_code = WhisperResultCode::NotInitialized;
-            result.error_message = string_to_c_char("Engine not initialized");
-            return result;
+        match audio_from_c(samples, sample_count, format, sample_rate, channels) {
+            Ok(audio) => transcribe_with_engine(&(*handle).engine, &audio),
+            Err(msg) => CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, &msg),
         }
-    };
+    })
+}
 
-    // Create audio buffer from samples
-    let samples_slice = std::slice::from_raw_parts(samples, sample_count);
-    let audio = AudioBuffer::from_samples(samples_slice.to_vec(), sample_rate);
+/// Destroys an engine created with `whisper_create`.
+///
+/// # Safety
+/// `handle` must have been returned by `whisper_create` and not yet destroyed.
+/// Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_destroy(handle: *mut WhisperHandle) {
+    ffi_guard(|| {
+        if handle.is_null() {
+            return;
+        }
 
-    match engine.transcribe(&audio) {
-        Ok(transcription) => {
-            result.text = string_to_c_char(&transcription.text);
-            result.language = string_to_c_char(&transcription.language);
-            result.segment_count = transcription.segments.len() as i32;
-            result.processing_time_ms = transcription.processing_time_ms;
-            result.audio_duration_ms = transcription.audio_duration_ms;
-            result.result_code = WhisperResultCode::Success;
+        let mut handle = Box::from_raw(handle);
+        handle.engine.shutdown();
+    })
+}
+
+/// Starts a live transcription session with its own engine, which calls
+/// `on_partial` with the transcript so far as audio is pushed.
+///
+/// Only one session runs at a time; starting another while one is active
+/// fails with `Busy`. The callback runs on the thread calling
+/// `whisper_stream_push` or `whisper_stream_stop`, must not call the other
+/// `whisper_stream_*` functions, and must not keep the result, which is
+/// freed when it returns.
+///
+/// # Safety
+/// - The `config` pointer must be valid and properly initialized.
+/// - `user_data` is passed to `on_partial` as is, and must stay usable from
+///   the pushing threads until `whisper_stream_stop` returns.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_stream_start(
+    config: *const CWhisperConfig,
+    on_partial: Option<extern "C" fn(*const CTranscriptionResult, *mut c_void)>,
+    user_data: *mut c_void,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if config.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Config is null");
         }
-        Err(e) => {
-            result.result_code = WhisperResultCode::TranscriptionFailed;
-            result.error_message = string_to_c_char(&e.to_string());
+        let Some(on_partial) = on_partial else {
+            return fail(WhisperResultCode::InvalidParameter, "Callback is null");
+        };
+
+        let mut stream = lock_stream();
+        if stream.is_some() {
+            return fail(WhisperResultCode::Busy, "A stream is already running");
         }
-    }
 
-    result
+        let engine = match config_from_c(&*config).and_then(create_engine) {
+            Ok(engine) => engine,
+            Err(code) => return code,
+        };
+        *stream = Some(FfiStream {
+            max_result_chars: engine.config().max_result_chars,
+            session: StreamingSession::new(Arc::new(engine), StreamingConfig::default()),
+            on_partial,
+            user_data,
+            pending_samples: 0,
+        });
+        WhisperResultCode::Success
+    })
+}
+
+/// Adds mono samples at `sample_rate` to the running stream.
+///
+/// Once half a second of audio has arrived since the last partial result,
+/// the stream is transcribed and `on_partial` called before returning.
+/// Fails with `NotInitialized` if no stream is running.
+///
+/// # Safety
+/// `samples` must be a valid pointer to `sample_count` f32 values.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_stream_push(
+    samples: *const f32,
+    sample_count: usize,
+    sample_rate: u32,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if samples.is_null() || sample_count == 0 {
+            return fail(WhisperResultCode::InvalidParameter, "Invalid audio samples");
+        }
+        if let Err(e) = validate_sample_rate(sample_rate) {
+            return fail(WhisperResultCode::InvalidParameter, &e.to_string());
+        }
+
+        let mut guard = lock_stream();
+        let Some(stream) = guard.as_mut() else {
+            return fail(WhisperResultCode::NotInitialized, "No stream running");
+        };
+
+        let samples = std::slice::from_raw_parts(samples, sample_count);
+        if let Err(e) = stream.session.push(samples, sample_rate) {
+            return fail(WhisperResultCode::TranscriptionFailed, &e.to_string());
+        }
+
+        // Counting samples rather than whole milliseconds per push keeps
+        // small buffers from rounding down to nothing
+        stream.pending_samples += sample_count as u64;
+        if stream.pending_samples < partial_interval_samples(sample_rate) {
+            return WhisperResultCode::Success;
+        }
+        stream.pending_samples = 0;
+
+        match stream.session.poll() {
+            Ok(result) => {
+                stream.emit(result);
+                WhisperResultCode::Success
+            }
+            Err(e) => fail(WhisperResultCode::TranscriptionFailed, &e.to_string()),
+        }
+    })
+}
+
+/// Number of samples at `sample_rate` between partial stream results.
+fn partial_interval_samples(sample_rate: u32) -> u64 {
+    STREAM_PARTIAL_INTERVAL_MS * sample_rate as u64 / 1000
+}
+
+/// Ends the running stream, transcribing any audio not yet committed and
+/// calling `on_partial` a last time with the final transcript, then releases
+/// the stream's engine.
+///
+/// Fails with `NotInitialized` if no stream is running.
+#[no_mangle]
+pub extern "C" fn whisper_stream_stop() -> WhisperResultCode {
+    ffi_guard(|| {
+        let Some(mut stream) = lock_stream().take() else {
+            return fail(WhisperResultCode::NotInitialized, "No stream running");
+        };
+
+        match stream.session.finish() {
+            Ok(result) => {
+                stream.emit(result);
+                WhisperResultCode::Success
+            }
+            Err(e) => fail(WhisperResultCode::TranscriptionFailed, &e.to_string()),
+        }
+    })
+}
+
+/// Transcribes interleaved audio samples.
+///
+/// Channels are averaged to mono before transcription; pass 1 for mono audio.
+///
+/// # Safety
+/// - `samples` must be a valid pointer to `sample_count` f32 values
+///   (`sample_count` counts samples across all channels).
+/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe(
+    samples: *const f32,
+    sample_count: usize,
+    sample_rate: u32,
+    channels: u16,
+) -> CTranscriptionResult {
+    whisper_transcribe_samples(samples.cast(), sample_count, WhisperAudioFormat::F32 as i32, sample_rate, channels)
+}
+
+/// Transcribes interleaved 16-bit PCM samples.
+///
+/// Channels are averaged to mono before transcription.
+///
+/// # Safety
+/// - `samples` must be a valid pointer to `sample_count` i16 values
+///   (`sample_count` counts samples across all channels).
+/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe_i16(
+    samples: *const i16,
+    sample_count: usize,
+    sample_rate: u32,
+    channels: u16,
+) -> CTranscriptionResult {
+    whisper_transcribe_samples(samples.cast(), sample_count, WhisperAudioFormat::I16 as i32, sample_rate, channels)
+}
+
+/// Transcribes interleaved samples in any `WhisperAudioFormat`.
+///
+/// `format` selects how `samples` is read; channels are averaged to mono
+/// before transcription.
+///
+/// # Safety
+/// - `samples` must be a valid pointer to `sample_count` values of the type
+///   named by `format` (`sample_count` counts samples across all channels).
+/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe_samples(
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    channels: u16,
+) -> CTranscriptionResult {
+    ffi_guard(|| match audio_from_c(samples, sample_count, format, sample_rate, channels) {
+        Ok(audio) => transcribe_audio(&audio),
+        Err(msg) => CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, &msg),
+    })
+}
+
+/// Transcribes like `whisper_transcribe_samples`, but fails with `Busy`
+/// instead of waiting if the global engine is in use, e.g. by a running
+/// transcription.
+///
+/// # Safety
+/// Same as `whisper_transcribe_samples`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_try_transcribe(
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    channels: u16,
+) -> CTranscriptionResult {
+    ffi_guard(|| {
+        let audio = match audio_from_c(samples, sample_count, format, sample_rate, channels) {
+            Ok(audio) => audio,
+            Err(msg) => return CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, &msg),
+        };
+
+        let engine_guard = match ENGINE.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                return CTranscriptionResult::failure(WhisperResultCode::Busy, "Engine is busy")
+            }
+        };
+        match engine_guard.as_ref() {
+            Some(engine) => transcribe_with_engine(engine, &audio),
+            None => CTranscriptionResult::failure(WhisperResultCode::NotInitialized, "Engine not initialized"),
+        }
+    })
 }
 
 /// Transcribes audio from a file.
 ///
+/// On Unix the path is used as raw bytes and need not be valid UTF-8.
+///
 /// # Safety
-/// - `file_path` must be a valid null-terminated UTF-8 string.
+/// - `file_path` must be a valid null-terminated string.
 /// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
 #[no_mangle]
 pub unsafe extern "C" fn whisper_transcribe_file(file_path: *const c_char) -> CTranscriptionResult {
-    let mut result = CTranscriptionResult::default();
+    ffi_guard(|| {
+        if file_path.is_null() {
+            return CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, "File path is null");
+        }
 
-    if file_path.is_null() {
-        result.result_code = WhisperResultCode::InvalidParameter;
-        result.error_message = string_to_c_char("File path is null");
-        return result;
-    }
+        transcribe_path_bytes(CStr::from_ptr(file_path).to_bytes())
+    })
+}
 
-    let path = match CStr::from_ptr(file_path).to_str() {
-        Ok(s) => s,
-        Err(_) => {
-            result.result_code = WhisperResultCode::InvalidParameter;
-            result.error_message = string_to_c_char("Invalid file path encoding");
-            return result;
+/// Transcribes audio from a file whose path is given as `path_len` bytes,
+/// without a terminator.
+///
+/// On Unix the bytes are used as-is, so paths that are not valid UTF-8 work
+/// (pass e.g. `FileManager.fileSystemRepresentation` from Swift).
+///
+/// # Safety
+/// - `path` must be a valid pointer to `path_len` bytes.
+/// - The returned `CTranscriptionResult` must be freed with `whisper_free_result`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe_file_bytes(path: *const u8, path_len: usize) -> CTranscriptionResult {
+    ffi_guard(|| {
+        if path.is_null() || path_len == 0 {
+            return CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, "File path is empty");
+        }
+
+        transcribe_path_bytes(std::slice::from_raw_parts(path, path_len))
+    })
+}
+
+/// Transcribes the file at a byte-encoded path with the global engine.
+fn transcribe_path_bytes(bytes: &[u8]) -> CTranscriptionResult {
+    let path = match path_from_bytes(bytes) {
+        Some(path) => path,
+        None => {
+            return CTranscriptionResult::failure(WhisperResultCode::InvalidParameter, "Invalid file path encoding");
         }
     };
 
-    let engine_guard = ENGINE.lock().unwrap();
+    let engine_guard = lock_engine();
     let engine = match engine_guard.as_ref() {
         Some(e) => e,
         None => {
-            result.result_code = WhisperResultCode::NotInitialized;
-            result.error_message = string_to_c_char("Engine not initialized");
-            return result;
+            return CTranscriptionResult::failure(WhisperResultCode::NotInitialized, "Engine not initialized");
         }
     };
 
-    match engine.transcribe_file(path) {
-        Ok(transcription) => {
-            result.text = string_to_c_char(&transcription.text);
-            result.language = string_to_c_char(&transcription.language);
-            result.segment_count = transcription.segments.len() as i32;
-            result.processing_time_ms = transcription.processing_time_ms;
-            result.audio_duration_ms = transcription.audio_duration_ms;
-            result.result_code = WhisperResultCode::Success;
-        }
-        Err(e) => {
-            result.result_code = WhisperResultCode::TranscriptionFailed;
-            result.error_message = string_to_c_char(&e.to_string());
-        }
-    }
+    to_c_result(engine.transcribe_file(path), engine.config().max_result_chars)
+}
 
-    result
+/// Transcribes `in_path` with the global engine and writes SubRip subtitles
+/// to `out_path`, replacing any existing file.
+///
+/// # Safety
+/// `in_path` and `out_path` must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_transcribe_file_to_srt(
+    in_path: *const c_char,
+    out_path: *const c_char,
+) -> WhisperResultCode {
+    ffi_guard(|| transcribe_file_to_subtitles(in_path, out_path, TranscriptionResult::to_srt))
 }
 
-/// Frees a transcription result.
+/// Transcribes `in_path` with the global engine and writes WebVTT subtitles
+/// to `out_path`, replacing any existing file.
 ///
 /// # Safety
-/// The `result` pointer must be valid and have been returned by a whisper_transcribe* function.
+/// `in_path` and `out_path` must be valid null-terminated strings.
 #[no_mangle]
-pub unsafe extern "C" fn whisper_free_result(result: *mut CTranscriptionResult) {
-    if result.is_null() {
-        return;
+pub unsafe extern "C" fn whisper_transcribe_file_to_vtt(
+    in_path: *const c_char,
+    out_path: *const c_char,
+) -> WhisperResultCode {
+    ffi_guard(|| transcribe_file_to_subtitles(in_path, out_path, TranscriptionResult::to_vtt))
+}
+
+/// Transcribes a file and writes it out with `render`.
+///
+/// # Safety
+/// Both paths must be null or valid null-terminated strings.
+unsafe fn transcribe_file_to_subtitles(
+    in_path: *const c_char,
+    out_path: *const c_char,
+    render: fn(&TranscriptionResult) -> String,
+) -> WhisperResultCode {
+    if in_path.is_null() || out_path.is_null() {
+        return fail(WhisperResultCode::InvalidParameter, "Input or output path is null");
     }
 
-    let result = &mut *result;
+    let (Some(in_path), Some(out_path)) = (
+        path_from_bytes(CStr::from_ptr(in_path).to_bytes()),
+        path_from_bytes(CStr::from_ptr(out_path).to_bytes()),
+    ) else {
+        return fail(WhisperResultCode::InvalidParameter, "Invalid file path encoding");
+    };
 
-    if !result.text.is_null() {
-        drop(CString::from_raw(result.text));
-        result.text = ptr::null_mut();
-    }
+    let engine_guard = lock_engine();
+    let Some(engine) = engine_guard.as_ref() else {
+        return fail(WhisperResultCode::NotInitialized, "Engine not initialized");
+    };
 
-    if !result.language.is_null() {
-        drop(CString::from_raw(result.language));
-        result.language = ptr::null_mut();
-    }
+    let result = match engine.transcribe_file(&in_path) {
+        Ok(result) => result,
+        Err(e) => return fail(WhisperResultCode::TranscriptionFailed, &e.to_string()),
+    };
 
-    if !result.error_message.is_null() {
-        drop(CString::from_raw(result.error_message));
-        result.error_message = ptr::null_mut();
+    match std::fs::write(&out_path, render(&result)) {
+        Ok(()) => WhisperResultCode::Success,
+        Err(e) => fail(
+            WhisperResultCode::Error,
+            &format!("Failed to write {}: {}", out_path.display(), e),
+        ),
     }
 }
 
-/// Shuts down the Whisper engine and releases resources.
+/// Reads the header of in-memory WAV data into `info` without decoding samples.
+///
+/// Returns `InvalidParameter` if the data is not a readable WAV header;
+/// `whisper_last_error` then has details.
+///
+/// # Safety
+/// - `data` must point to `len` readable bytes.
+/// - `info` must point to writable memory for a `CAudioInfo`.
 #[no_mangle]
-pub extern "C" fn whisper_shutdown() {
-    let mut engine_guard = ENGINE.lock().unwrap();
-    if let Some(mut engine) = engine_guard.take() {
-        engine.shutdown();
-    }
+pub unsafe extern "C" fn whisper_probe_wav(
+    data: *const u8,
+    len: usize,
+    info: *mut CAudioInfo,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if data.is_null() || info.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Data or info is null");
+        }
+
+        let bytes = std::slice::from_raw_parts(data, len);
+        let metadata = match crate::audio::probe_wav(bytes) {
+            Ok(metadata) => metadata,
+            Err(e) => return fail(WhisperResultCode::InvalidParameter, &e.to_string()),
+        };
+
+        *info = CAudioInfo {
+            sample_rate: metadata.sample_rate,
+            channels: metadata.channels,
+            bits_per_sample: metadata.bits_per_sample.unwrap_or(0),
+            duration_ms: metadata.duration_ms,
+        };
+        WhisperResultCode::Success
+    })
 }
 
-/// Returns the library version.
-#[no_mangle]
+/// Reads a model file's header into `metadata` without loading the model.
+///
+/// Returns `ModelNotFound` if the file does not exist and `Error` if it is not
+/// a whisper ggml model; `whisper_last_error` then has details.
+///
+/// # Safety
+/// - `path` must be a valid null-terminated UTF-8 string.
+/// - `metadata` must point to writable memory for a `CModelMetadata`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_inspect_model(
+    path: *const c_char,
+    metadata: *mut CModelMetadata,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if path.is_null() || metadata.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Path or metadata is null");
+        }
+
+        let Some(path) = path_from_bytes(CStr::from_ptr(path).to_bytes()) else {
+            return fail(WhisperResultCode::InvalidParameter, "Invalid file path encoding");
+        };
+
+        let info = match crate::model::inspect_model(&path) {
+            Ok(info) => info,
+            Err(e @ WhisperError::ModelNotFound(_)) => {
+                return fail(WhisperResultCode::ModelNotFound, &e.to_string())
+            }
+            Err(e) => return fail(WhisperResultCode::Error, &e.to_string()),
+        };
+
+        let mut quantization = [0 as c_char; 16];
+        for (dst, &src) in quantization.iter_mut().zip(info.quantization.as_bytes().iter().take(15)) {
+            *dst = src as c_char;
+        }
+
+        *metadata = CModelMetadata {
+            model_size: info.model_size.map_or(-1, |size| size as i32),
+            multilingual: info.multilingual,
+            n_vocab: info.n_vocab,
+            n_audio_layer: info.n_audio_layer,
+            n_text_layer: info.n_text_layer,
+            n_mels: info.n_mels,
+            ftype: info.ftype,
+            quantization,
+        };
+        WhisperResultCode::Success
+    })
+}
+
+/// Computes the RMS energy envelope of mono `samples` for visualization.
+///
+/// `format` is a `WhisperAudioFormat` code. Frames are `window_ms` long and
+/// start every `hop_ms`. The total frame count is stored in `out_len`, and
+/// as many frames as fit are written to `out`. Pass a null `out` to query
+/// the required capacity. Zero samples give an empty envelope.
+///
+/// # Safety
+/// - `samples` must be a valid pointer to `sample_count` values of the type
+///   named by `format`.
+/// - `out`, if not null, must point to writable memory for `out_capacity` f32 values.
+/// - `out_len` must point to writable memory for a `usize`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_energy_envelope(
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    window_ms: u32,
+    hop_ms: u32,
+    out: *mut f32,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if out_len.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Output length is null");
+        }
+
+        // No samples make an empty envelope rather than an error
+        let envelope = match audio_from_c_allow_empty(samples, sample_count, format, sample_rate, 1) {
+            Ok(audio) => audio.energy_envelope(window_ms, hop_ms),
+            Err(msg) => return fail(WhisperResultCode::InvalidParameter, &msg),
+        };
+
+        if !out.is_null() {
+            let written = envelope.len().min(out_capacity);
+            ptr::copy_nonoverlapping(envelope.as_ptr(), out, written);
+        }
+        *out_len = envelope.len();
+        WhisperResultCode::Success
+    })
+}
+
+/// Frees a transcription result.
+///
+/// # Safety
+/// The `result` pointer must be valid and have been returned by a whisper_transcribe* function.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_free_result(result: *mut CTranscriptionResult) {
+    ffi_guard(|| {
+        if result.is_null() {
+            return;
+        }
+
+        let result = &mut *result;
+
+        if !result.text.is_null() {
+            drop(CString::from_raw(result.text));
+            result.text = ptr::null_mut();
+        }
+
+        if !result.language.is_null() {
+            drop(CString::from_raw(result.language));
+            result.language = ptr::null_mut();
+        }
+
+        if !result.error_message.is_null() {
+            drop(CString::from_raw(result.error_message));
+            result.error_message = ptr::null_mut();
+        }
+    })
+}
+
+/// Returns the number of segments in the last transcription of the global
+/// engine, or -1 if there is none (not initialized, or cleared with
+/// `whisper_clear_result`).
+#[no_mangle]
+pub extern "C" fn whisper_get_segment_count() -> i32 {
+    ffi_guard(|| {
+        lock_engine()
+            .as_ref()
+            .and_then(|engine| engine.last_segment_count())
+            .map_or(-1, |count| count as i32)
+    })
+}
+
+/// Copies segment `index` of the last transcription of the global engine
+/// into `segment`.
+///
+/// Returns `NotInitialized` if there is no stored result, including after
+/// `whisper_clear_result`, and `InvalidParameter` if `index` is out of range.
+///
+/// # Safety
+/// - `segment` must point to writable memory for a `CSegment`.
+/// - On success, `segment->text` must be freed with `whisper_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_get_segment(index: usize, segment: *mut CSegment) -> WhisperResultCode {
+    ffi_guard(|| {
+        if segment.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Segment is null");
+        }
+
+        let engine_guard = lock_engine();
+        let Some(engine) = engine_guard.as_ref().filter(|e| e.last_segment_count().is_some()) else {
+            return fail(WhisperResultCode::NotInitialized, "No transcription result stored");
+        };
+        let Some(found) = engine.last_segment(index) else {
+            return fail(WhisperResultCode::InvalidParameter, "Segment index out of range");
+        };
+
+        *segment = CSegment {
+            start_ms: found.start_ms,
+            end_ms: found.end_ms,
+            text: string_to_c_char(&found.text),
+            confidence: found.confidence,
+            speaker_id: found.speaker_id.map_or(-1, |id| id as i32),
+        };
+        WhisperResultCode::Success
+    })
+}
+
+/// Copies the words of segment `index` of the last transcription of the
+/// global engine into `words`, which has room for `max_words` entries.
+///
+/// `count` always receives the segment's word count (0 unless the engine was
+/// configured with word timestamps). If it exceeds `max_words`, nothing is
+/// copied and `BufferTooSmall` is returned, so passing 0 queries the size.
+/// Other failures are as for `whisper_get_segment`.
+///
+/// # Safety
+/// - `words` must point to writable memory for `max_words` `CWord`s, or be
+///   null when `max_words` is 0.
+/// - `count` must point to writable memory for a `usize`.
+/// - On success, each `words[i].text` must be freed with `whisper_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_get_segment_words(
+    index: usize,
+    words: *mut CWord,
+    max_words: usize,
+    count: *mut usize,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if count.is_null() || (words.is_null() && max_words > 0) {
+            return fail(WhisperResultCode::InvalidParameter, "Words or count is null");
+        }
+
+        let engine_guard = lock_engine();
+        let Some(engine) = engine_guard.as_ref().filter(|e| e.last_segment_count().is_some()) else {
+            return fail(WhisperResultCode::NotInitialized, "No transcription result stored");
+        };
+        let Some(segment) = engine.last_segment(index) else {
+            return fail(WhisperResultCode::InvalidParameter, "Segment index out of range");
+        };
+
+        *count = segment.words.len();
+        if segment.words.len() > max_words {
+            return fail(
+                WhisperResultCode::BufferTooSmall,
+                &format!("Segment has {} words, buffer holds {}", segment.words.len(), max_words),
+            );
+        }
+
+        for (i, word) in segment.words.iter().enumerate() {
+            *words.add(i) = CWord {
+                start_ms: word.start_ms,
+                end_ms: word.end_ms,
+                text: string_to_c_char(&word.text),
+                probability: word.probability,
+            };
+        }
+        WhisperResultCode::Success
+    })
+}
+
+/// Frees the global engine's stored last transcription.
+///
+/// Segment accessors then report no result until the next transcription.
+/// Safe to call when nothing is stored or the engine is not initialized.
+#[no_mangle]
+pub extern "C" fn whisper_clear_result() {
+    ffi_guard(|| {
+        if let Some(engine) = lock_engine().as_ref() {
+            engine.clear_last_result();
+        }
+    })
+}
+
+/// Shuts down the Whisper engine and releases resources.
+#[no_mangle]
+pub extern "C" fn whisper_shutdown() {
+    ffi_guard(|| {
+        let mut engine_guard = lock_engine();
+        if let Some(mut engine) = engine_guard.take() {
+            engine.shutdown();
+        }
+    })
+}
+
+/// Returns the library version.
+#[no_mangle]
 pub extern "C" fn whisper_version() -> *const c_char {
-    static VERSION: &[u8] = b"0.1.0\0";
-    VERSION.as_ptr() as *const c_char
+    ffi_guard(|| {
+        static VERSION: &[u8] = b"0.1.0\0";
+        VERSION.as_ptr() as *const c_char
+    })
+}
+
+/// Estimates the processing time in milliseconds for `sample_count` samples
+/// at `sample_rate`, without transcribing.
+///
+/// Returns 0 if the engine is not initialized or the sample rate is 0.
+#[no_mangle]
+pub extern "C" fn whisper_estimate_time_ms(sample_count: usize, sample_rate: u32) -> u64 {
+    ffi_guard(|| {
+        if sample_rate == 0 {
+            return 0;
+        }
+
+        let engine_guard = lock_engine();
+        let engine = match engine_guard.as_ref() {
+            Some(e) => e,
+            None => return 0,
+        };
+
+        let audio_seconds = sample_count as f64 / sample_rate as f64;
+        (audio_seconds * engine.realtime_factor_estimate() * 1000.0) as u64
+    })
+}
+
+/// Returns how long the global engine took to load its model, in milliseconds.
+///
+/// Returns 0 if the engine is not initialized.
+#[no_mangle]
+pub extern "C" fn whisper_model_load_time_ms() -> u64 {
+    ffi_guard(|| {
+        let engine_guard = lock_engine();
+        engine_guard
+            .as_ref()
+            .and_then(|e| e.model_load_time_ms())
+            .unwrap_or(0)
+    })
+}
+
+/// Returns how long the engine behind `handle` took to load its model, in milliseconds.
+///
+/// Returns 0 if `handle` is null.
+///
+/// # Safety
+/// `handle` must be null or have been returned by `whisper_create` and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_handle_model_load_time_ms(handle: *const WhisperHandle) -> u64 {
+    ffi_guard(|| {
+        if handle.is_null() {
+            return 0;
+        }
+        (*handle).engine.model_load_time_ms().unwrap_or(0)
+    })
+}
+
+/// Unloads the global engine's model after `timeout_ms` of inactivity
+/// (0 = keep it resident). It is reloaded transparently on the next transcription.
+#[no_mangle]
+pub extern "C" fn whisper_set_idle_unload(timeout_ms: u64) -> WhisperResultCode {
+    ffi_guard(|| {
+        let engine_guard = lock_engine();
+        match engine_guard.as_ref() {
+            Some(engine) => {
+                engine.set_idle_unload(idle_timeout(timeout_ms));
+                WhisperResultCode::Success
+            }
+            None => fail(WhisperResultCode::NotInitialized, "Engine not initialized"),
+        }
+    })
+}
+
+/// Unloads the model of the engine behind `handle` after `timeout_ms` of
+/// inactivity (0 = keep it resident).
+///
+/// # Safety
+/// `handle` must be null or have been returned by `whisper_create` and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_handle_set_idle_unload(
+    handle: *const WhisperHandle,
+    timeout_ms: u64,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if handle.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Handle is null");
+        }
+        (*handle).engine.set_idle_unload(idle_timeout(timeout_ms));
+        WhisperResultCode::Success
+    })
+}
+
+/// Returns the message of the most recent failed FFI call on the calling thread,
+/// or null if none has failed.
+///
+/// The string is owned by the library and must not be freed. It stays valid
+/// until the next failing FFI call on the same thread replaces it; copy it
+/// if it is needed longer.
+#[no_mangle]
+pub extern "C" fn whisper_last_error() -> *const c_char {
+    ffi_guard(|| {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+/// Sets the directory where models are looked up by default, for every
+/// engine in the process. Pass null to restore the default directory.
+///
+/// Returns `InvalidParameter`, keeping the current directory, if `path` is
+/// not an existing directory.
+///
+/// # Safety
+/// `path` must be null or a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_set_models_dir(path: *const c_char) -> WhisperResultCode {
+    ffi_guard(|| {
+        let dir = if path.is_null() {
+            None
+        } else {
+            match path_from_bytes(CStr::from_ptr(path).to_bytes()) {
+                Some(dir) => Some(dir),
+                None => return fail(WhisperResultCode::InvalidParameter, "Invalid directory path encoding"),
+            }
+        };
+
+        match crate::config::set_models_dir(dir.as_deref()) {
+            Ok(()) => WhisperResultCode::Success,
+            Err(e) => fail(WhisperResultCode::InvalidParameter, &e.to_string()),
+        }
+    })
+}
+
+/// Checks the model files for `count` model sizes, writing one status per
+/// size to `out_codes`, without loading any weights.
+///
+/// Models are looked up in `dir`, or in the default models directory if
+/// `dir` is null. A status is `Success` for a valid model, `ModelNotFound`
+/// for a missing file, `InvalidParameter` for an unknown size code and
+/// `Error` for a file that is not a whisper ggml model.
+///
+/// # Safety
+/// - `sizes` must point to `count` model size codes.
+/// - `dir` must be null or a valid null-terminated string.
+/// - `out_codes` must point to writable memory for `count` `WhisperResultCode`s.
+#[no_mangle]
+pub unsafe extern "C" fn whisper_preload_models(
+    sizes: *const i32,
+    count: usize,
+    dir: *const c_char,
+    out_codes: *mut WhisperResultCode,
+) -> WhisperResultCode {
+    ffi_guard(|| {
+        if sizes.is_null() || out_codes.is_null() {
+            return fail(WhisperResultCode::InvalidParameter, "Sizes or output codes are null");
+        }
+
+        let dir = if dir.is_null() {
+            match crate::config::default_models_dir() {
+                Ok(dir) => dir,
+                Err(e) => return fail(WhisperResultCode::Error, &e.to_string()),
+            }
+        } else {
+            match path_from_bytes(CStr::from_ptr(dir).to_bytes()) {
+                Some(dir) => dir,
+                None => return fail(WhisperResultCode::InvalidParameter, "Invalid directory path encoding"),
+            }
+        };
+
+        let sizes = std::slice::from_raw_parts(sizes, count);
+        let out_codes = std::slice::from_raw_parts_mut(out_codes, count);
+        let known: Vec<ModelSize> = sizes.iter().filter_map(|&code| model_size_from_c(code)).collect();
+        let mut statuses = crate::model::preload_models(&known, &dir).into_iter();
+        for (&code, out) in sizes.iter().zip(out_codes.iter_mut()) {
+            *out = match model_size_from_c(code).and_then(|_| statuses.next()) {
+                None => WhisperResultCode::InvalidParameter,
+                Some((_, Ok(()))) => WhisperResultCode::Success,
+                Some((_, Err(WhisperError::ModelNotFound(_)))) => WhisperResultCode::ModelNotFound,
+                Some((_, Err(_))) => WhisperResultCode::Error,
+            };
+        }
+        WhisperResultCode::Success
+    })
+}
+
+/// Checks whether the model file for `model_size` exists at its default path,
+/// without loading it.
+///
+/// Returns false for unknown sizes or if the models directory cannot be determined.
+#[no_mangle]
+pub extern "C" fn whisper_model_available(model_size: i32) -> bool {
+    ffi_guard(|| {
+        model_size_from_c(model_size)
+            .and_then(|size| crate::config::default_model_path(size).ok())
+            .is_some_and(|path| path.is_file())
+    })
 }
 
 /// Checks if the engine is initialized.
 #[no_mangle]
 pub extern "C" fn whisper_is_initialized() -> bool {
-    let engine_guard = ENGINE.lock().unwrap();
-    engine_guard
-        .as_ref()
-        .map(|e| e.is_initialized())
-        .unwrap_or(false)
+    ffi_guard(|| {
+        let engine_guard = lock_engine();
+        engine_guard
+            .as_ref()
+            .map(|e| e.is_initialized())
+            .unwrap_or(false)
+    })
+}
+
+/// Checks if the loaded model supports languages other than English.
+///
+/// Returns false for English-only (`.en`) models and when the engine is not
+/// initialized.
+#[no_mangle]
+pub extern "C" fn whisper_is_model_multilingual() -> bool {
+    ffi_guard(|| {
+        let engine_guard = lock_engine();
+        engine_guard
+            .as_ref()
+            .map(|e| e.is_multilingual())
+            .unwrap_or(false)
+    })
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// What an FFI function returns when its body panics.
+trait PanicFallback {
+    /// Builds the failure value; `message` has already been set as the last error.
+    fn on_panic(message: &str) -> Self;
+}
+
+impl PanicFallback for WhisperResultCode {
+    fn on_panic(_message: &str) -> Self {
+        WhisperResultCode::Error
+    }
+}
+
+impl PanicFallback for CTranscriptionResult {
+    fn on_panic(message: &str) -> Self {
+        CTranscriptionResult::failure(WhisperResultCode::Error, message)
+    }
+}
+
+impl<T> PanicFallback for *mut T {
+    fn on_panic(_message: &str) -> Self {
+        ptr::null_mut()
+    }
+}
+
+impl<T> PanicFallback for *const T {
+    fn on_panic(_message: &str) -> Self {
+        ptr::null()
+    }
+}
+
+impl PanicFallback for () {
+    fn on_panic(_message: &str) -> Self {}
+}
+
+impl PanicFallback for bool {
+    fn on_panic(_message: &str) -> Self {
+        false
+    }
+}
+
+impl PanicFallback for u64 {
+    fn on_panic(_message: &str) -> Self {
+        0
+    }
+}
+
+impl PanicFallback for i32 {
+    fn on_panic(_message: &str) -> Self {
+        -1
+    }
+}
+
+/// Runs an FFI function body, turning a panic into its failure value.
+///
+/// Unwinding into the Swift caller is undefined behavior, so every exported
+/// function goes through this. The panic message becomes the last error.
+fn ffi_guard<T: PanicFallback>(body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = WhisperError::FfiError(format!("panic: {}", panic_message(&*payload))).to_string();
+        tracing::error!("{}", message);
+        set_last_error(&message);
+        T::on_panic(&message)
+    })
+}
+
+/// Extracts the message from a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Locks the global engine, recovering it if a panic poisoned the lock.
+/// Makes `engine` the global engine, keeping its results for the segment
+/// accessors.
+fn set_global_engine(engine: TranscriptionEngine) {
+    engine.set_keep_last_result(true);
+    *lock_engine() = Some(engine);
+}
+
+fn lock_engine() -> MutexGuard<'static, Option<TranscriptionEngine>> {
+    ENGINE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Locks the streaming session, recovering it if a panic poisoned the lock.
+fn lock_stream() -> MutexGuard<'static, Option<FfiStream>> {
+    STREAM.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Converts a platform path from raw bytes.
+///
+/// Unix paths are arbitrary bytes; elsewhere they must be UTF-8.
+fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        std::str::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}
+
+/// Maps a C audio format code to a `WhisperAudioFormat`.
+fn audio_format_from_c(code: i32) -> Option<WhisperAudioFormat> {
+    match code {
+        0 => Some(WhisperAudioFormat::F32),
+        1 => Some(WhisperAudioFormat::I16),
+        _ => None,
+    }
+}
+
+/// Builds an audio buffer from interleaved C samples in the given format.
+///
+/// # Safety
+/// `samples` must be null or point to `sample_count` values of the type
+/// named by `format`.
+unsafe fn audio_from_c(
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<AudioBuffer, String> {
+    if sample_count == 0 {
+        return Err("Invalid audio samples".to_string());
+    }
+    audio_from_c_allow_empty(samples, sample_count, format, sample_rate, channels)
+}
+
+/// Builds an audio buffer like [`audio_from_c`], accepting zero samples.
+///
+/// # Safety
+/// As for [`audio_from_c`].
+unsafe fn audio_from_c_allow_empty(
+    samples: *const c_void,
+    sample_count: usize,
+    format: i32,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<AudioBuffer, String> {
+    if samples.is_null() || channels == 0 {
+        return Err("Invalid audio samples".to_string());
+    }
+
+    validate_sample_rate(sample_rate).map_err(|e| e.to_string())?;
+
+    match audio_format_from_c(format) {
+        Some(WhisperAudioFormat::F32) => {
+            let samples = std::slice::from_raw_parts(samples.cast::<f32>(), sample_count);
+            Ok(AudioBuffer::from_interleaved(samples, channels, sample_rate))
+        }
+        Some(WhisperAudioFormat::I16) => {
+            let samples = std::slice::from_raw_parts(samples.cast::<i16>(), sample_count);
+            Ok(AudioBuffer::from_i16_interleaved(samples, channels, sample_rate))
+        }
+        None => Err(format!("Unknown audio format: {}", format)),
+    }
+}
+
+/// Maps a C model size code to a `ModelSize`.
+fn model_size_from_c(code: i32) -> Option<ModelSize> {
+    match code {
+        0 => Some(ModelSize::Tiny),
+        1 => Some(ModelSize::Base),
+        2 => Some(ModelSize::Small),
+        3 => Some(ModelSize::Medium),
+        4 => Some(ModelSize::Large),
+        5 => Some(ModelSize::LargeV2),
+        6 => Some(ModelSize::LargeV3),
+        7 => Some(ModelSize::LargeV3Turbo),
+        8 => Some(ModelSize::TinyEn),
+        9 => Some(ModelSize::BaseEn),
+        10 => Some(ModelSize::SmallEn),
+        11 => Some(ModelSize::MediumEn),
+        _ => None,
+    }
+}
+
+/// Converts a C configuration to a Rust configuration.
+///
+/// # Safety
+/// String pointers in `c_config` must be null or valid null-terminated strings.
+unsafe fn config_from_c(c_config: &CWhisperConfig) -> Result<WhisperConfig, WhisperResultCode> {
+    let model_path = if c_config.model_path.is_null() {
+        String::new()
+    } else {
+        match CStr::from_ptr(c_config.model_path).to_str() {
+            Ok(s) => s.to_string(),
+            Err(_) => {
+                return Err(fail(
+                    WhisperResultCode::InvalidParameter,
+                    "Model path is not valid UTF-8",
+                ))
+            }
+        }
+    };
+
+    let language = if c_config.language.is_null() {
+        "auto".to_string()
+    } else {
+        match CStr::from_ptr(c_config.language).to_str() {
+            Ok(s) => s.to_string(),
+            Err(_) => "auto".to_string(),
+        }
+    };
+
+    let model_size = model_size_from_c(c_config.model_size).unwrap_or(ModelSize::Base);
+
+    Ok(WhisperConfig {
+        model_path,
+        model_size,
+        language: crate::config::LanguageConfig {
+            source: language,
+            translate_to_english: c_config.translate,
+        },
+        n_threads: c_config.n_threads,
+        use_gpu: c_config.use_gpu,
+        ..Default::default()
+    })
+}
+
+/// Creates and initializes an engine, mapping failures to result codes.
+fn create_engine(config: WhisperConfig) -> Result<TranscriptionEngine, WhisperResultCode> {
+    let mut engine = TranscriptionEngine::new(config);
+
+    match engine.initialize() {
+        Ok(()) => Ok(engine),
+        Err(e @ WhisperError::ModelNotFound(_)) => {
+            Err(fail(WhisperResultCode::ModelNotFound, &e.to_string()))
+        }
+        Err(e) => Err(fail(WhisperResultCode::Error, &e.to_string())),
+    }
+}
+
+/// Records `message` as the calling thread's last error.
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Records `message` as the last error and returns `code`.
+fn fail(code: WhisperResultCode, message: &str) -> WhisperResultCode {
+    set_last_error(message);
+    code
+}
+
+/// Converts a C idle timeout, where 0 disables unloading.
+fn idle_timeout(timeout_ms: u64) -> Option<std::time::Duration> {
+    (timeout_ms > 0).then(|| std::time::Duration::from_millis(timeout_ms))
+}
+
+/// Transcribes a buffer with the global engine and converts the outcome.
+fn transcribe_audio(audio: &AudioBuffer) -> CTranscriptionResult {
+    let engine_guard = lock_engine();
+    match engine_guard.as_ref() {
+        Some(engine) => transcribe_with_engine(engine, audio),
+        None => CTranscriptionResult::failure(
+            WhisperResultCode::NotInitialized,
+            "Engine not initialized",
+        ),
+    }
+}
+
+/// Transcribes a buffer with `engine` and converts the outcome.
+fn transcribe_with_engine(engine: &TranscriptionEngine, audio: &AudioBuffer) -> CTranscriptionResult {
+    to_c_result(engine.transcribe(audio), engine.config().max_result_chars)
+}
+
+/// Converts a transcription outcome to a C result, capping the text at
+/// `max_chars` characters (0 = unlimited).
+fn to_c_result(
+    outcome: crate::error::Result<TranscriptionResult>,
+    max_chars: usize,
+) -> CTranscriptionResult {
+    let mut result = CTranscriptionResult::default();
+
+    match outcome {
+        Ok(transcription) => {
+            let (text, truncated) = cap_text(&transcription.text, max_chars);
+            result.text = string_to_c_char(&text);
+            result.text_truncated = truncated;
+            result.language = string_to_c_char(&transcription.language);
+            result.segment_count = transcription.segments.len() as i32;
+            result.processing_time_ms = transcription.processing_time_ms;
+            result.resample_time_ms = transcription.resample_time_ms;
+            result.inference_time_ms = transcription.inference_time_ms;
+            result.audio_duration_ms = transcription.audio_duration_ms;
+            result.processed_duration_ms = transcription.processed_duration_ms;
+            result.had_speech = transcription.had_speech;
+            result.timed_out = transcription.timed_out;
+            result.greedy_fallback = transcription.greedy_fallback;
+            result.average_confidence = transcription.average_confidence;
+            result.segments_truncated = transcription.segments_truncated;
+            result.result_code = WhisperResultCode::Success;
+        }
+        Err(e) => {
+            return CTranscriptionResult::failure(WhisperResultCode::TranscriptionFailed, &e.to_string());
+        }
+    }
+
+    result
+}
+
+/// Marker appended to text cut at the result length limit.
+const TRUNCATION_MARKER: &str = "…";
+
+/// Cuts `text` to at most `max_chars` characters plus a marker (0 = unlimited).
+///
+/// Returns the text and whether it was truncated.
+fn cap_text(text: &str, max_chars: usize) -> (Cow<'_, str>, bool) {
+    if max_chars == 0 {
+        return (Cow::Borrowed(text), false);
+    }
+
+    let kept = crate::text::truncate_chars(text, max_chars);
+    if kept.len() < text.len() {
+        (Cow::Owned(format!("{}{}", kept, TRUNCATION_MARKER)), true)
+    } else {
+        (Cow::Borrowed(text), false)
+    }
+}
+
 /// Converts a Rust string to a C string pointer.
 fn string_to_c_char(s: &str) -> *mut c_char {
     match CString::new(s) {
@@ -337,3 +1657,176 @@ fn string_to_c_char(s: &str) -> *mut c_char {
         Err(_) => ptr::null_mut(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let message = whisper_last_error();
+        (!message.is_null()).then(|| unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_last_error() {
+        assert!(matches!(
+            unsafe { whisper_init(ptr::null()) },
+            WhisperResultCode::InvalidParameter
+        ));
+        assert_eq!(last_error().as_deref(), Some("Config is null"));
+
+        let json = CString::new(r#"{"model_path": "/nonexistent/model.bin"}"#).unwrap();
+        assert!(matches!(
+            unsafe { whisper_init_from_json(json.as_ptr()) },
+            WhisperResultCode::ModelNotFound
+        ));
+        assert!(last_error().unwrap().contains("/nonexistent/model.bin"));
+
+        // Errors are per thread
+        let other = std::thread::spawn(last_error).join().unwrap();
+        assert_eq!(other, None);
+    }
+
+    #[test]
+    fn test_ffi_guard_catches_panics() {
+        let code = ffi_guard(|| -> WhisperResultCode { panic!("resample edge case") });
+        assert!(matches!(code, WhisperResultCode::Error));
+        assert_eq!(last_error().as_deref(), Some("FFI error: panic: resample edge case"));
+
+        let result = ffi_guard(|| -> CTranscriptionResult { panic!("{}", 42) });
+        assert!(matches!(result.result_code, WhisperResultCode::Error));
+        assert!(ffi_guard(|| -> *mut WhisperHandle { panic!() }).is_null());
+        assert_eq!(ffi_guard(|| 7u64), 7);
+    }
+
+    #[test]
+    fn test_init_checked_returns_error() {
+        let mut error = ptr::null_mut();
+        let code = unsafe { whisper_init_checked(ptr::null(), &mut error) };
+        assert!(matches!(code, WhisperResultCode::InvalidParameter));
+        assert_eq!(unsafe { CStr::from_ptr(error) }.to_str(), Ok("Config is null"));
+        unsafe { whisper_free_string(error) };
+
+        let code = unsafe { whisper_init_checked(ptr::null(), ptr::null_mut()) };
+        assert!(matches!(code, WhisperResultCode::InvalidParameter));
+    }
+
+    #[test]
+    fn test_audio_from_c_formats() {
+        let pcm: [i16; 4] = [16384, -16384, 0, 32767];
+        let audio = unsafe { audio_from_c(pcm.as_ptr().cast(), 4, WhisperAudioFormat::I16 as i32, 16000, 2) }.unwrap();
+        assert_eq!(audio.samples().len(), 2);
+        assert_eq!(audio.samples()[0], 0.0);
+
+        let floats = [0.25f32, -0.5];
+        let audio = unsafe { audio_from_c(floats.as_ptr().cast(), 2, WhisperAudioFormat::F32 as i32, 16000, 1) }.unwrap();
+        assert_eq!(audio.samples(), &floats);
+
+        let unknown = unsafe { audio_from_c(floats.as_ptr().cast(), 2, 9, 16000, 1) };
+        assert_eq!(unknown.unwrap_err(), "Unknown audio format: 9");
+        assert!(unsafe { audio_from_c(ptr::null(), 2, 0, 16000, 1) }.is_err());
+    }
+
+    #[test]
+    fn test_segment_accessors_without_result() {
+        whisper_clear_result();
+        assert_eq!(whisper_get_segment_count(), -1);
+        let mut segment = std::mem::MaybeUninit::<CSegment>::uninit();
+        let code = unsafe { whisper_get_segment(0, segment.as_mut_ptr()) };
+        assert!(matches!(code, WhisperResultCode::NotInitialized));
+
+        let mut count = 0;
+        let code = unsafe { whisper_get_segment_words(0, ptr::null_mut(), 0, &mut count) };
+        assert!(matches!(code, WhisperResultCode::NotInitialized));
+        let code = unsafe { whisper_get_segment_words(0, ptr::null_mut(), 4, &mut count) };
+        assert!(matches!(code, WhisperResultCode::InvalidParameter));
+    }
+
+    #[test]
+    fn test_try_transcribe_busy() {
+        let samples = [0.0f32; 1600];
+        let guard = lock_engine();
+        let mut result = unsafe { whisper_try_transcribe(samples.as_ptr().cast(), samples.len(), 0, 16000, 1) };
+        drop(guard);
+        assert!(matches!(result.result_code, WhisperResultCode::Busy));
+        unsafe { whisper_free_result(&mut result) };
+    }
+
+    #[test]
+    fn test_stream_lifecycle() {
+        extern "C" fn count_results(result: *const CTranscriptionResult, user_data: *mut c_void) {
+            assert!(matches!(unsafe { &*result }.result_code, WhisperResultCode::Success));
+            unsafe { *user_data.cast::<u32>() += 1 };
+        }
+
+        let samples = [0.0f32; 1600];
+        let code = unsafe { whisper_stream_push(samples.as_ptr(), samples.len(), 16000) };
+        assert!(matches!(code, WhisperResultCode::NotInitialized));
+        assert!(matches!(whisper_stream_stop(), WhisperResultCode::NotInitialized));
+        let code = unsafe { whisper_stream_start(ptr::null(), Some(count_results), ptr::null_mut()) };
+        assert!(matches!(code, WhisperResultCode::InvalidParameter));
+
+        // Stopping flushes a final result through the callback
+        let mut calls = 0u32;
+        *lock_stream() = Some(FfiStream {
+            session: StreamingSession::new(Arc::new(TranscriptionEngine::with_defaults()), StreamingConfig::default()),
+            on_partial: count_results,
+            user_data: (&mut calls as *mut u32).cast(),
+            max_result_chars: 0,
+            pending_samples: 0,
+        });
+        assert!(matches!(whisper_stream_stop(), WhisperResultCode::Success));
+        assert_eq!(calls, 1);
+        assert!(lock_stream().is_none());
+    }
+
+    #[test]
+    fn test_partial_interval_samples() {
+        assert_eq!(partial_interval_samples(16000), 8000);
+        assert_eq!(partial_interval_samples(44100), 22050);
+        assert_eq!(partial_interval_samples(8000), 4000);
+    }
+
+    #[test]
+    fn test_preload_models_codes() {
+        let dir = std::env::temp_dir().join("whisper-core-ffi-preload-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(ModelSize::Small.filename()), b"not a model").unwrap();
+        let dir_c = CString::new(dir.to_str().unwrap()).unwrap();
+
+        // Unknown codes keep their place between the known ones
+        let sizes = [2, 99, 0];
+        let mut codes = [WhisperResultCode::Success, WhisperResultCode::Success, WhisperResultCode::Success];
+        let code = unsafe { whisper_preload_models(sizes.as_ptr(), sizes.len(), dir_c.as_ptr(), codes.as_mut_ptr()) };
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(code, WhisperResultCode::Success));
+        assert!(matches!(codes[0], WhisperResultCode::Error));
+        assert!(matches!(codes[1], WhisperResultCode::InvalidParameter));
+        assert!(matches!(codes[2], WhisperResultCode::ModelNotFound));
+    }
+
+    #[test]
+    fn test_energy_envelope_empty() {
+        let samples = [0.5f32; 1];
+        let mut len = usize::MAX;
+        let code = unsafe {
+            whisper_energy_envelope(samples.as_ptr().cast(), 0, 0, 16000, 20, 10, ptr::null_mut(), 0, &mut len)
+        };
+        assert!(matches!(code, WhisperResultCode::Success));
+        assert_eq!(len, 0);
+
+        let code = unsafe {
+            whisper_energy_envelope(ptr::null(), 0, 0, 16000, 20, 10, ptr::null_mut(), 0, &mut len)
+        };
+        assert!(matches!(code, WhisperResultCode::InvalidParameter));
+    }
+
+    #[test]
+    fn test_cap_text() {
+        assert_eq!(cap_text("hello", 0), (Cow::Borrowed("hello"), false));
+        assert_eq!(cap_text("hello", 5), (Cow::Borrowed("hello"), false));
+        assert_eq!(cap_text("hello", 3).0, "hel…");
+        assert!(cap_text("hello", 3).1);
+        assert_eq!(cap_text("日本語です", 2).0, "日本…");
+    }
+}
@@ -8,8 +8,16 @@ pub mod audio;
 pub mod config;
 pub mod error;
 pub mod ffi;
+pub mod model;
+pub mod streaming;
+pub mod text;
 pub mod transcription;
+pub mod vad;
 
-pub use config::WhisperConfig;
+pub use config::{TranscriptionOptions, WhisperConfig};
 pub use error::{WhisperError, Result};
-pub use transcription::{TranscriptionEngine, TranscriptionResult, Segment};
+pub use streaming::{StreamingConfig, StreamingSession};
+pub use transcription::{
+    FailedRange, Segment, TimestampPrecision, TokenInfo, TranscriptionEngine, TranscriptionPlan,
+    TranscriptionResult, Word,
+};
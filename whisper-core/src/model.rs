@@ -0,0 +1,206 @@
+//! Inspection of whisper.cpp ggml model files.
+
+use crate::config::ModelSize;
+use crate::error::{Result, WhisperError};
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+
+/// Magic number at the start of a ggml model file ("ggml", little-endian).
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// Vocabulary size of multilingual models; English-only models have one fewer token.
+const MULTILINGUAL_VOCAB: i32 = 51865;
+
+/// ggml tensor types encode a quantization version in multiples of this.
+const GGML_QNT_VERSION_FACTOR: i32 = 1000;
+
+/// Header fields of a whisper ggml model file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelMetadata {
+    /// Model variant inferred from the architecture, if it matches a known one.
+    ///
+    /// Large and large-v2 share an architecture; [`inspect_model`] tells them
+    /// apart by file name, and reports `Large` when the name does not say.
+    pub model_size: Option<ModelSize>,
+    /// Whether the model supports languages other than English.
+    pub multilingual: bool,
+    /// Number of vocabulary tokens.
+    pub n_vocab: i32,
+    /// Number of audio encoder layers.
+    pub n_audio_layer: i32,
+    /// Number of text decoder layers.
+    pub n_text_layer: i32,
+    /// Number of mel frequency bins.
+    pub n_mels: i32,
+    /// Raw ggml weight type (without the quantization version).
+    pub ftype: i32,
+    /// Weight format name, e.g. "f16" or "q5_0".
+    pub quantization: &'static str,
+}
+
+/// Reads a model's header without loading its weights.
+///
+/// Fails with `ModelNotFound` if the file does not exist and `ModelLoadError`
+/// if it is not a whisper ggml model.
+pub fn inspect_model(path: impl AsRef<Path>) -> Result<ModelMetadata> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => WhisperError::ModelNotFound(path.display().to_string()),
+        _ => WhisperError::IoError(e),
+    })?;
+    let mut metadata = read_header(std::io::BufReader::new(file))?;
+
+    let named = path.file_name().and_then(|name| ModelSize::from_filename(&name.to_string_lossy()));
+    if metadata.model_size == Some(ModelSize::Large) && named == Some(ModelSize::LargeV2) {
+        metadata.model_size = named;
+    }
+    Ok(metadata)
+}
+
+/// Checks that the model file for each of `sizes` exists in `dir` and has a
+/// valid ggml header, without loading any weights.
+///
+/// Returns one status per size, in the order given.
+pub fn preload_models(sizes: &[ModelSize], dir: &Path) -> Vec<(ModelSize, Result<()>)> {
+    sizes
+        .iter()
+        .map(|&size| (size, inspect_model(dir.join(size.filename())).map(|_| ())))
+        .collect()
+}
+
+/// Parses the magic and hyperparameters at the start of a ggml model.
+fn read_header<R: Read>(mut reader: R) -> Result<ModelMetadata> {
+    let mut header = [0u8; 48];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| WhisperError::ModelLoadError("File too short for a ggml header".to_string()))?;
+
+    let field = |index: usize| {
+        let offset = index * 4;
+        i32::from_le_bytes(header[offset..offset + 4].try_into().unwrap())
+    };
+
+    if field(0) as u32 != GGML_MAGIC {
+        return Err(WhisperError::ModelLoadError(
+            "Not a whisper ggml model (bad magic)".to_string(),
+        ));
+    }
+
+    // Hyperparameters follow the magic in whisper.cpp's order
+    let n_vocab = field(1);
+    let n_audio_layer = field(5);
+    let n_text_layer = field(9);
+    let n_mels = field(10);
+    let ftype = field(11) % GGML_QNT_VERSION_FACTOR;
+
+    Ok(ModelMetadata {
+        model_size: infer_model_size(n_audio_layer, n_text_layer, n_mels, n_vocab >= MULTILINGUAL_VOCAB),
+        multilingual: n_vocab >= MULTILINGUAL_VOCAB,
+        n_vocab,
+        n_audio_layer,
+        n_text_layer,
+        n_mels,
+        ftype,
+        quantization: quantization_name(ftype),
+    })
+}
+
+/// Maps an architecture to the model variant it belongs to.
+fn infer_model_size(n_audio_layer: i32, n_text_layer: i32, n_mels: i32, multilingual: bool) -> Option<ModelSize> {
+    match (n_audio_layer, n_text_layer, n_mels, multilingual) {
+        (4, 4, 80, true) => Some(ModelSize::Tiny),
+        (6, 6, 80, true) => Some(ModelSize::Base),
+        (12, 12, 80, true) => Some(ModelSize::Small),
+        (24, 24, 80, true) => Some(ModelSize::Medium),
+        (4, 4, 80, false) => Some(ModelSize::TinyEn),
+        (6, 6, 80, false) => Some(ModelSize::BaseEn),
+        (12, 12, 80, false) => Some(ModelSize::SmallEn),
+        (24, 24, 80, false) => Some(ModelSize::MediumEn),
+        (32, 32, 80, _) => Some(ModelSize::Large),
+        (32, 32, 128, _) => Some(ModelSize::LargeV3),
+        (32, 4, 128, _) => Some(ModelSize::LargeV3Turbo),
+        _ => None,
+    }
+}
+
+/// Returns the name of a ggml weight type.
+fn quantization_name(ftype: i32) -> &'static str {
+    match ftype {
+        0 => "f32",
+        1 => "f16",
+        2 => "q4_0",
+        3 => "q4_1",
+        7 => "q8_0",
+        8 => "q5_0",
+        9 => "q5_1",
+        10 => "q2_k",
+        11 => "q3_k",
+        12 => "q4_k",
+        13 => "q5_k",
+        14 => "q6_k",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Builds a ggml header from the hyperparameters after the magic.
+    fn header(hparams: [i32; 11]) -> Vec<u8> {
+        let mut bytes = GGML_MAGIC.to_le_bytes().to_vec();
+        for value in hparams {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_read_header() {
+        let base = header([51865, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+        let metadata = read_header(Cursor::new(base)).unwrap();
+        assert_eq!(metadata.model_size, Some(ModelSize::Base));
+        assert!(metadata.multilingual);
+        assert_eq!(metadata.quantization, "f16");
+
+        // English-only turbo, quantized with version 2
+        let turbo = header([51864, 1500, 1280, 20, 32, 448, 1280, 20, 4, 128, 2008]);
+        let metadata = read_header(Cursor::new(turbo)).unwrap();
+        assert_eq!(metadata.model_size, Some(ModelSize::LargeV3Turbo));
+        assert!(!metadata.multilingual);
+        assert_eq!(metadata.ftype, 8);
+        assert_eq!(metadata.quantization, "q5_0");
+
+        let base_en = header([51864, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+        let metadata = read_header(Cursor::new(base_en)).unwrap();
+        assert_eq!(metadata.model_size, Some(ModelSize::BaseEn));
+        assert!(!ModelSize::BaseEn.is_multilingual());
+    }
+
+    #[test]
+    fn test_read_header_invalid() {
+        let mut bad_magic = header([51865, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+        bad_magic[0] = b'x';
+        assert!(matches!(read_header(Cursor::new(bad_magic)), Err(WhisperError::ModelLoadError(_))));
+        assert!(matches!(read_header(Cursor::new(vec![0u8; 8])), Err(WhisperError::ModelLoadError(_))));
+        assert!(matches!(inspect_model("/nonexistent/model.bin"), Err(WhisperError::ModelNotFound(_))));
+    }
+
+    #[test]
+    fn test_preload_models() {
+        let dir = std::env::temp_dir().join("whisper-core-preload-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = header([51865, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+        std::fs::write(dir.join(ModelSize::Base.filename()), base).unwrap();
+        std::fs::write(dir.join(ModelSize::Small.filename()), b"not a model").unwrap();
+
+        let statuses = preload_models(&[ModelSize::Base, ModelSize::Small, ModelSize::Tiny], &dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(statuses.len(), 3);
+        assert!(matches!(statuses[0], (ModelSize::Base, Ok(()))));
+        assert!(matches!(statuses[1], (ModelSize::Small, Err(WhisperError::ModelLoadError(_)))));
+        assert!(matches!(statuses[2], (ModelSize::Tiny, Err(WhisperError::ModelNotFound(_)))));
+    }
+}
@@ -0,0 +1,376 @@
+//! Live transcription over a sliding audio window.
+
+use crate::audio::{AudioBuffer, WHISPER_SAMPLE_RATE};
+use crate::error::Result;
+use crate::transcription::{Segment, TranscriptionEngine, TranscriptionResult};
+use crate::vad;
+use std::sync::Arc;
+
+/// Callback receiving each complete utterance.
+type UtteranceCallback = Box<dyn FnMut(&TranscriptionResult) + Send>;
+
+/// Configuration for a streaming session.
+#[derive(Debug, Clone)]
+pub struct StreamingConfig {
+    /// Trailing audio duration whose segments are considered unstable.
+    ///
+    /// Segments ending within this distance of the newest audio are reported
+    /// as partial; earlier ones are committed and never change again.
+    pub partial_tail_ms: i64,
+    /// Maximum window length before older audio is force-committed.
+    ///
+    /// In utterance mode, speech running past it is emitted as an utterance
+    /// without waiting for silence.
+    pub max_window_ms: i64,
+    /// Silence after speech that ends an utterance, when an
+    /// [`on_utterance`](StreamingSession::on_utterance) callback is set.
+    pub utterance_silence_ms: i64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            partial_tail_ms: 2000,
+            max_window_ms: 25_000,
+            utterance_silence_ms: 800,
+        }
+    }
+}
+
+/// Incremental transcription of pushed audio.
+///
+/// Audio is accumulated in a window that is re-transcribed on every
+/// [`poll`](Self::poll). Segments that are far enough from the end of the
+/// window are committed (`is_final = true`) and their audio is dropped from
+/// the window; the rest are returned as partial (`is_final = false`).
+pub struct StreamingSession {
+    engine: Arc<TranscriptionEngine>,
+    config: StreamingConfig,
+    /// Uncommitted audio (mono, 16kHz).
+    window: AudioBuffer,
+    /// Stream position of the first sample in `window`, in samples.
+    window_offset: usize,
+    /// Segments that will no longer change.
+    committed: Vec<Segment>,
+    language: String,
+    processing_time_ms: u64,
+    resample_time_ms: u64,
+    inference_time_ms: u64,
+    had_speech: bool,
+    on_utterance: Option<UtteranceCallback>,
+}
+
+impl StreamingSession {
+    /// Creates a session transcribing with `engine`.
+    pub fn new(engine: Arc<TranscriptionEngine>, config: StreamingConfig) -> Self {
+        Self {
+            engine,
+            config,
+            window: AudioBuffer::new(),
+            window_offset: 0,
+            committed: Vec::new(),
+            language: String::new(),
+            processing_time_ms: 0,
+            resample_time_ms: 0,
+            inference_time_ms: 0,
+            had_speech: false,
+            on_utterance: None,
+        }
+    }
+
+    /// Switches the session to utterance mode: once speech is followed by
+    /// `utterance_silence_ms` of silence, [`push`](Self::push) transcribes
+    /// the window, commits it, and passes the utterance to `callback`.
+    ///
+    /// The utterance's segments are in stream time. Silence before speech is
+    /// dropped without being transcribed.
+    pub fn on_utterance(&mut self, callback: impl FnMut(&TranscriptionResult) + Send + 'static) {
+        self.on_utterance = Some(Box::new(callback));
+    }
+
+    /// Appends mono samples at `sample_rate` to the session.
+    pub fn push(&mut self, samples: &[f32], sample_rate: u32) -> Result<()> {
+        let audio = AudioBuffer::from_samples(samples.to_vec(), sample_rate)
+            .resample(WHISPER_SAMPLE_RATE)?;
+        self.window.append(audio.samples());
+
+        if self.on_utterance.is_some() {
+            self.detect_utterance_end()?;
+        }
+        Ok(())
+    }
+
+    /// Emits the window as an utterance if speech in it has ended or the
+    /// window has outgrown `max_window_ms`, or drops it if it holds only
+    /// silence.
+    fn detect_utterance_end(&mut self) -> Result<()> {
+        let threshold = self.engine.config().vad_threshold;
+        let silence_ms = self.config.utterance_silence_ms;
+        let samples_to_ms = |samples: usize| (samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64;
+
+        match vad::trailing_silence(self.window.samples(), WHISPER_SAMPLE_RATE, threshold) {
+            None => {
+                // Keep a little silence so an onset is not clipped
+                let excess_ms = self.window_duration_ms() - silence_ms;
+                if excess_ms > 0 {
+                    self.trim_window(excess_ms);
+                }
+            }
+            Some(trailing)
+                if samples_to_ms(trailing) >= silence_ms || self.window_duration_ms() > self.config.max_window_ms =>
+            {
+                let utterance = self.finish_utterance()?;
+                if let Some(callback) = self.on_utterance.as_mut() {
+                    callback(&utterance);
+                }
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Transcribes and commits the whole window, returning just its result.
+    fn finish_utterance(&mut self) -> Result<TranscriptionResult> {
+        let window_ms = self.window_duration_ms();
+        let mut result = self.engine.transcribe(&self.window)?;
+        self.record(&result);
+
+        result.segments = self.to_stream_time(result.segments, true);
+        self.committed.extend(result.segments.iter().cloned());
+        self.trim_window(window_ms);
+        Ok(result)
+    }
+
+    /// Transcribes the current window and returns committed and partial segments.
+    pub fn poll(&mut self) -> Result<TranscriptionResult> {
+        let window_ms = self.window_duration_ms();
+        let result = self.engine.transcribe(&self.window)?;
+        self.record(&result);
+
+        // A window that has grown too long is committed in full
+        let tail_ms = if window_ms > self.config.max_window_ms {
+            0
+        } else {
+            self.config.partial_tail_ms
+        };
+
+        let (stable, partial) = split_stable(result.segments, window_ms, tail_ms);
+        let partial = self.to_stream_time(partial, false);
+        let committed_until = stable.last().map(|s| s.end_ms);
+
+        self.commit(stable);
+        if let Some(end_ms) = committed_until {
+            self.trim_window(end_ms);
+        }
+
+        Ok(self.snapshot(partial))
+    }
+
+    /// Transcribes any remaining audio, commits everything, and returns the
+    /// final result for the whole stream.
+    pub fn finish(&mut self) -> Result<TranscriptionResult> {
+        if !self.window.is_empty() {
+            let window_ms = self.window_duration_ms();
+            let result = self.engine.transcribe(&self.window)?;
+            self.record(&result);
+            self.commit(result.segments);
+            self.trim_window(window_ms);
+        }
+
+        Ok(self.snapshot(Vec::new()))
+    }
+
+    /// Returns the segments committed so far.
+    pub fn committed(&self) -> &[Segment] {
+        &self.committed
+    }
+
+    /// Returns the duration of the uncommitted window in milliseconds.
+    fn window_duration_ms(&self) -> i64 {
+        (self.window.duration_seconds() * 1000.0) as i64
+    }
+
+    /// Returns the stream time of the first sample in the window.
+    fn window_offset_ms(&self) -> i64 {
+        (self.window_offset as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64
+    }
+
+    fn record(&mut self, result: &TranscriptionResult) {
+        self.processing_time_ms += result.processing_time_ms;
+        self.resample_time_ms += result.resample_time_ms;
+        self.inference_time_ms += result.inference_time_ms;
+        self.had_speech |= result.had_speech;
+        if self.language.is_empty() && !result.text.is_empty() {
+            self.language = result.language.clone();
+        }
+    }
+
+    /// Shifts window-relative segments to stream time.
+    fn to_stream_time(&self, segments: Vec<Segment>, is_final: bool) -> Vec<Segment> {
+        segments
+            .into_iter()
+            .map(|mut segment| {
+                segment.shift(self.window_offset_ms());
+                segment.is_final = is_final;
+                segment
+            })
+            .collect()
+    }
+
+    /// Commits window-relative segments.
+    fn commit(&mut self, segments: Vec<Segment>) {
+        let segments = self.to_stream_time(segments, true);
+        self.committed.extend(segments);
+    }
+
+    /// Drops the first `ms` of the window, advancing the window offset by
+    /// the samples actually dropped.
+    fn trim_window(&mut self, ms: i64) {
+        let samples = (ms.max(0) as usize * WHISPER_SAMPLE_RATE as usize / 1000).min(self.window.len());
+        let remaining = self.window.samples()[samples..].to_vec();
+        self.window = AudioBuffer::from_samples(remaining, WHISPER_SAMPLE_RATE);
+        self.window_offset += samples;
+    }
+
+    /// Builds a result from the committed segments followed by `partial`,
+    /// which must already be in stream time.
+    fn snapshot(&self, partial: Vec<Segment>) -> TranscriptionResult {
+        let mut segments = self.committed.clone();
+        segments.extend(partial);
+
+        let text = self.engine.transcript_text(&segments, &self.language);
+
+        let audio_duration_ms = (self.window_offset_ms() + self.window_duration_ms()).max(0) as u64;
+
+        let mut result = TranscriptionResult {
+            text,
+            segments,
+            language: self.language.clone(),
+            processing_time_ms: self.processing_time_ms,
+            resample_time_ms: self.resample_time_ms,
+            inference_time_ms: self.inference_time_ms,
+            audio_duration_ms,
+            processed_duration_ms: audio_duration_ms,
+            segments_truncated: false,
+            is_preview: false,
+            had_speech: self.had_speech,
+            failed_ranges: Vec::new(),
+            raw_text: None,
+            cancelled: false,
+            timed_out: false,
+            greedy_fallback: false,
+            average_confidence: 0.0,
+        };
+        result.average_confidence = result.average_confidence();
+        result
+    }
+}
+
+/// Splits window-relative segments into those ending before the unstable
+/// tail and those inside it.
+fn split_stable(segments: Vec<Segment>, window_ms: i64, tail_ms: i64) -> (Vec<Segment>, Vec<Segment>) {
+    let stable_until = window_ms - tail_ms;
+    let split = segments
+        .iter()
+        .position(|s| s.end_ms > stable_until)
+        .unwrap_or(segments.len());
+
+    let mut stable = segments;
+    let partial = stable.split_off(split);
+    (stable, partial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::WhisperError;
+
+    #[test]
+    fn test_split_stable() {
+        let segments = vec![
+            Segment::new(0, 1000, " a".to_string()),
+            Segment::new(1000, 2500, " b".to_string()),
+            Segment::new(2500, 3000, " c".to_string()),
+        ];
+
+        let (stable, partial) = split_stable(segments.clone(), 4000, 2000);
+        assert_eq!(stable.len(), 1);
+        assert_eq!(partial.len(), 2);
+
+        // A segment ending exactly at the tail boundary is stable
+        let (stable, partial) = split_stable(segments.clone(), 4500, 2000);
+        assert_eq!(stable.len(), 2);
+        assert_eq!(partial.len(), 1);
+
+        let (stable, partial) = split_stable(segments, 3000, 0);
+        assert_eq!(stable.len(), 3);
+        assert!(partial.is_empty());
+    }
+
+    #[test]
+    fn test_utterance_mode_drops_leading_silence() {
+        let engine = Arc::new(TranscriptionEngine::with_defaults());
+        let mut session = StreamingSession::new(engine, StreamingConfig::default());
+        session.on_utterance(|_| panic!("silence is not an utterance"));
+
+        session.push(&vec![0.0; 48_000], 16000).unwrap();
+        assert_eq!(session.window_duration_ms(), 800);
+        assert_eq!(session.window_offset_ms(), 2200);
+    }
+
+    #[test]
+    fn test_utterance_mode_caps_window() {
+        let engine = Arc::new(TranscriptionEngine::with_defaults());
+        let config = StreamingConfig {
+            max_window_ms: 1000,
+            ..StreamingConfig::default()
+        };
+        let mut session = StreamingSession::new(engine, config);
+        session.on_utterance(|_| {});
+
+        // Unbroken speech: no utterance yet within the limit
+        session.push(&vec![0.3; 12_000], 16000).unwrap();
+        assert_eq!(session.window_duration_ms(), 750);
+
+        // Past it the window is transcribed, which needs the unloaded model
+        let err = session.push(&vec![0.3; 8_000], 16000).unwrap_err();
+        assert!(matches!(err, WhisperError::ContextInitError(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_commit_and_snapshot_offsets() {
+        let engine = Arc::new(TranscriptionEngine::with_defaults());
+        let mut session = StreamingSession::new(engine, StreamingConfig::default());
+        session.push(&vec![0.0; 64_000], 16000).unwrap();
+
+        // First poll: one stable segment, window trimmed to its end
+        session.commit(vec![Segment::new(500, 1500, " Hello".to_string())]);
+        session.trim_window(1500);
+
+        // Second poll: window-relative timestamps restart at zero
+        let partial = session.to_stream_time(vec![Segment::new(0, 500, " wor".to_string())], false);
+        session.commit(vec![Segment::new(0, 400, " big".to_string())]);
+        let snapshot = session.snapshot(partial);
+
+        assert_eq!(session.window.len(), 40_000);
+        assert_eq!(snapshot.text, "Hello big wor");
+        assert_eq!(snapshot.audio_duration_ms, 4000);
+        let times: Vec<_> = snapshot.segments.iter().map(|s| (s.start_ms, s.is_final)).collect();
+        assert_eq!(times, vec![(500, true), (1500, true), (1500, false)]);
+    }
+
+    #[test]
+    fn test_trim_window_past_end() {
+        let engine = Arc::new(TranscriptionEngine::with_defaults());
+        let mut session = StreamingSession::new(engine, StreamingConfig::default());
+        session.push(&vec![0.0; 16_000], 16000).unwrap();
+
+        // Only the second of audio actually dropped moves the offset
+        session.trim_window(3000);
+        assert_eq!(session.window.len(), 0);
+        assert_eq!(session.window_offset_ms(), 1000);
+
+        session.push(&vec![0.0; 8_000], 16000).unwrap();
+        assert_eq!(session.snapshot(Vec::new()).audio_duration_ms, 1500);
+    }
+}
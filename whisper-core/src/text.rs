@@ -0,0 +1,268 @@
+//! Text utilities for transcription output.
+
+/// Truncates `s` to at most `max_bytes` bytes without splitting a UTF-8 character.
+///
+/// If `max_bytes` falls inside a multibyte character, the cut moves back to
+/// the start of that character.
+pub fn truncate_on_char_boundary(s: &str, max_bytes: usize) -> &str {
+    if max_bytes >= s.len() {
+        return s;
+    }
+
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Truncates `s` to at most `max_chars` characters.
+pub fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => truncate_on_char_boundary(s, end),
+        None => s,
+    }
+}
+
+/// Returns the end of `s`, at most `max_chars` characters long.
+pub fn tail_chars(s: &str, max_chars: usize) -> &str {
+    let start = match max_chars.checked_sub(1) {
+        Some(n) => s.char_indices().rev().nth(n).map_or(0, |(start, _)| start),
+        None => s.len(),
+    };
+    &s[start..]
+}
+
+/// Joins segment texts into a full transcript.
+///
+/// Each piece is trimmed, empty pieces are skipped, and the rest are joined
+/// with single spaces, regardless of the leading spaces Whisper emits.
+pub fn join_segment_texts<'a>(texts: impl IntoIterator<Item = &'a str>) -> String {
+    join_segment_texts_with(texts, true)
+}
+
+/// Joins segment texts like [`join_segment_texts`], but concatenates them
+/// directly when `with_spaces` is false, for scripts without word spaces.
+pub fn join_segment_texts_with<'a>(texts: impl IntoIterator<Item = &'a str>, with_spaces: bool) -> String {
+    texts
+        .into_iter()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(if with_spaces { " " } else { "" })
+}
+
+/// Returns whether text in `language` (a Whisper language code) separates
+/// words with spaces.
+///
+/// False for Chinese, Japanese, Thai, Lao, Khmer, Burmese and Tibetan.
+pub fn uses_word_spaces(language: &str) -> bool {
+    !matches!(language, "zh" | "yue" | "ja" | "th" | "lo" | "km" | "my" | "bo")
+}
+
+/// Returns whether `text` has any letters or digits, in any script.
+///
+/// CJK ideographs and kana count as letters, so this is false only for
+/// fragments made of punctuation, symbols and whitespace, like "." or "。".
+pub fn has_word_characters(text: &str) -> bool {
+    text.chars().any(char::is_alphanumeric)
+}
+
+/// One step of a word-level alignment between a reference and a hypothesis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordEdit {
+    /// The word appears in both.
+    Equal(String),
+    /// A reference word was replaced by a different hypothesis word.
+    Substitute { reference: String, hypothesis: String },
+    /// A reference word is missing from the hypothesis.
+    Delete(String),
+    /// The hypothesis has a word the reference does not.
+    Insert(String),
+}
+
+/// Aligns the whitespace-separated words of two texts with the fewest edits.
+pub fn diff_words(reference: &str, hypothesis: &str) -> Vec<WordEdit> {
+    let reference: Vec<&str> = reference.split_whitespace().collect();
+    let hypothesis: Vec<&str> = hypothesis.split_whitespace().collect();
+    let (n, m) = (reference.len(), hypothesis.len());
+
+    // distance[i][j]: edits turning the first i reference words into the first j hypothesis words
+    let mut distance = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in distance.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distance[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let substitution = distance[i - 1][j - 1] + usize::from(reference[i - 1] != hypothesis[j - 1]);
+            distance[i][j] = substitution
+                .min(distance[i - 1][j] + 1)
+                .min(distance[i][j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        let diagonal = i > 0
+            && j > 0
+            && distance[i][j] == distance[i - 1][j - 1] + usize::from(reference[i - 1] != hypothesis[j - 1]);
+        if diagonal {
+            edits.push(if reference[i - 1] == hypothesis[j - 1] {
+                WordEdit::Equal(reference[i - 1].to_string())
+            } else {
+                WordEdit::Substitute {
+                    reference: reference[i - 1].to_string(),
+                    hypothesis: hypothesis[j - 1].to_string(),
+                }
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && distance[i][j] == distance[i - 1][j] + 1 {
+            edits.push(WordEdit::Delete(reference[i - 1].to_string()));
+            i -= 1;
+        } else {
+            edits.push(WordEdit::Insert(hypothesis[j - 1].to_string()));
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+/// Returns the word error rate of `hypothesis` against `reference`:
+/// substitutions, deletions and insertions over the number of reference words.
+///
+/// Words are compared exactly; apply [`normalize_for_wer`] to both texts
+/// first to ignore case and punctuation. An empty reference gives 0.0 for an
+/// empty hypothesis and 1.0 otherwise.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> f32 {
+    let edits = diff_words(reference, hypothesis);
+    let errors = edits.iter().filter(|e| !matches!(e, WordEdit::Equal(_))).count();
+    let reference_words = edits.iter().filter(|e| !matches!(e, WordEdit::Insert(_))).count();
+
+    match reference_words {
+        0 => f32::from(u8::from(errors > 0)),
+        n => errors as f32 / n as f32,
+    }
+}
+
+/// Lowercases `text` and drops punctuation, keeping letters, digits,
+/// apostrophes inside words, and whitespace.
+pub fn normalize_for_wer(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .chars()
+                .filter(|&c| c.is_alphanumeric() || c == '\'')
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_segment_texts() {
+        assert_eq!(join_segment_texts([" Hello", " world."]), "Hello world.");
+        assert_eq!(join_segment_texts(["Hello ", "  world ", " ", ""]), "Hello world");
+        assert_eq!(join_segment_texts(Vec::<&str>::new()), "");
+        assert_eq!(join_segment_texts_with([" 今日は", " いい天気です。"], false), "今日はいい天気です。");
+    }
+
+    #[test]
+    fn test_uses_word_spaces() {
+        assert!(uses_word_spaces("en"));
+        assert!(uses_word_spaces("ko"));
+        assert!(!uses_word_spaces("ja"));
+        assert!(!uses_word_spaces("zh"));
+        assert!(!uses_word_spaces("th"));
+    }
+
+    #[test]
+    fn test_has_word_characters() {
+        assert!(has_word_characters(" Hello."));
+        assert!(has_word_characters("こんにちは。"));
+        assert!(has_word_characters("42"));
+        assert!(!has_word_characters(" ."));
+        assert!(!has_word_characters("。"));
+        assert!(!has_word_characters("... ♪"));
+        assert!(!has_word_characters(""));
+    }
+
+    #[test]
+    fn test_word_error_rate() {
+        assert_eq!(word_error_rate("the cat sat", "the cat sat"), 0.0);
+        // One substitution and one deletion over four reference words
+        assert_eq!(word_error_rate("the cat sat down", "the bat sat"), 0.5);
+        assert_eq!(word_error_rate("hello", "hello there world"), 2.0);
+        assert_eq!(word_error_rate("", ""), 0.0);
+        assert_eq!(word_error_rate("", "noise"), 1.0);
+
+        let reference = normalize_for_wer("Hello, World! It's me.");
+        assert_eq!(reference, "hello world it's me");
+        assert_eq!(word_error_rate(&reference, &normalize_for_wer("hello world, it's me")), 0.0);
+    }
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(
+            diff_words("a b c", "a x c d"),
+            vec![
+                WordEdit::Equal("a".to_string()),
+                WordEdit::Substitute { reference: "b".to_string(), hypothesis: "x".to_string() },
+                WordEdit::Equal("c".to_string()),
+                WordEdit::Insert("d".to_string()),
+            ]
+        );
+        assert_eq!(diff_words("a b", "b"), vec![WordEdit::Delete("a".to_string()), WordEdit::Equal("b".to_string())]);
+    }
+
+    #[test]
+    fn test_truncate_ascii() {
+        assert_eq!(truncate_on_char_boundary("hello", 3), "hel");
+        assert_eq!(truncate_on_char_boundary("hello", 5), "hello");
+        assert_eq!(truncate_on_char_boundary("hello", 10), "hello");
+        assert_eq!(truncate_on_char_boundary("hello", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_cjk() {
+        // Each character is 3 bytes
+        let s = "日本語";
+        assert_eq!(truncate_on_char_boundary(s, 3), "日");
+        assert_eq!(truncate_on_char_boundary(s, 4), "日");
+        assert_eq!(truncate_on_char_boundary(s, 5), "日");
+        assert_eq!(truncate_on_char_boundary(s, 6), "日本");
+    }
+
+    #[test]
+    fn test_truncate_emoji() {
+        // "a" (1 byte) + 👋 (4 bytes) + "b"
+        let s = "a👋b";
+        assert_eq!(truncate_on_char_boundary(s, 1), "a");
+        assert_eq!(truncate_on_char_boundary(s, 2), "a");
+        assert_eq!(truncate_on_char_boundary(s, 4), "a");
+        assert_eq!(truncate_on_char_boundary(s, 5), "a👋");
+        assert_eq!(truncate_on_char_boundary(s, 6), "a👋b");
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("a👋日本", 2), "a👋");
+        assert_eq!(truncate_chars("a👋日本", 4), "a👋日本");
+        assert_eq!(truncate_chars("a👋日本", 0), "");
+        assert_eq!(tail_chars("a👋日本", 2), "日本");
+        assert_eq!(tail_chars("a👋日本", 3), "👋日本");
+        assert_eq!(tail_chars("a👋日本", 9), "a👋日本");
+        assert_eq!(tail_chars("a👋日本", 0), "");
+    }
+}
@@ -1,12 +1,21 @@
 //! Transcription engine using Whisper.
 
-use crate::audio::AudioBuffer;
-use crate::config::WhisperConfig;
+use crate::audio::{validate_sample_rate, AudioBuffer, ResampleQuality, WHISPER_SAMPLE_RATE};
+use crate::config::{TranscriptionOptions, WhisperConfig};
 use crate::error::{Result, WhisperError};
+use crate::vad;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use std::sync::Arc;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+    WhisperSysContext, WhisperSysState, WhisperTokenData,
+};
 
 /// A single transcription segment with timing information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,8 +29,49 @@ pub struct Segment {
     pub text: String,
     /// Confidence score (0.0 - 1.0).
     pub confidence: f32,
+    /// Mean log probability of the segment's text tokens (always <= 0.0),
+    /// as in whisper.cpp's JSON output.
+    #[serde(default)]
+    pub avg_logprob: f32,
     /// Speaker ID if diarization is enabled.
     pub speaker_id: Option<u32>,
+    /// Whether the segment is settled. Streaming sessions emit partial
+    /// segments (`false`) that may still change on the next poll.
+    pub is_final: bool,
+    /// Raw decoder tokens, populated only when `include_tokens` is enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<TokenInfo>,
+    /// Text exactly as decoded, before any cleanup, populated only when
+    /// `keep_raw_segment_text` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_text: Option<String>,
+    /// Per-word timing, populated only when `word_timestamps` is enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+}
+
+/// A single decoded token within a segment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenInfo {
+    /// Vocabulary token id.
+    pub id: i32,
+    /// Token text; special tokens render as e.g. `[_BEG_]`.
+    pub text: String,
+    /// Probability the decoder assigned to the token (0.0 - 1.0).
+    pub probability: f32,
+}
+
+/// A word of a segment with its own timing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    /// Start time in milliseconds.
+    pub start_ms: i64,
+    /// End time in milliseconds.
+    pub end_ms: i64,
+    /// Word text, with attached punctuation but no surrounding whitespace.
+    pub text: String,
+    /// Mean probability of the word's tokens (0.0 - 1.0).
+    pub probability: f32,
 }
 
 impl Segment {
@@ -32,7 +82,12 @@ impl Segment {
             end_ms,
             text,
             confidence: 1.0,
+            avg_logprob: 0.0,
             speaker_id: None,
+            is_final: true,
+            tokens: Vec::new(),
+            raw_text: None,
+            words: Vec::new(),
         }
     }
 
@@ -40,8 +95,55 @@ impl Segment {
     pub fn duration_ms(&self) -> i64 {
         self.end_ms - self.start_ms
     }
+
+    /// Extends the segment over the `next` one, appending its text, words
+    /// and tokens. Scores are averaged weighted by duration.
+    fn absorb(&mut self, next: Segment) {
+        let (own, other) = (self.duration_ms().max(0) as f32, next.duration_ms().max(0) as f32);
+        if own + other > 0.0 {
+            self.confidence = (self.confidence * own + next.confidence * other) / (own + other);
+            self.avg_logprob = (self.avg_logprob * own + next.avg_logprob * other) / (own + other);
+        }
+
+        self.text = match (self.text.trim().is_empty(), next.text.trim().is_empty()) {
+            (_, true) => std::mem::take(&mut self.text),
+            (true, false) => next.text,
+            (false, false) => format!("{} {}", self.text.trim_end(), next.text.trim_start()),
+        };
+        if let (Some(raw), Some(next_raw)) = (self.raw_text.as_mut(), next.raw_text) {
+            raw.push_str(&next_raw);
+        }
+        self.start_ms = self.start_ms.min(next.start_ms);
+        self.end_ms = self.end_ms.max(next.end_ms);
+        self.is_final &= next.is_final;
+        self.tokens.extend(next.tokens);
+        self.words.extend(next.words);
+    }
+
+    /// Moves the segment and its words by `offset_ms`.
+    pub fn shift(&mut self, offset_ms: i64) {
+        self.start_ms += offset_ms;
+        self.end_ms += offset_ms;
+        for word in &mut self.words {
+            word.start_ms += offset_ms;
+            word.end_ms += offset_ms;
+        }
+    }
+}
+
+/// Granularity of timestamps in [`TranscriptionResult::to_timestamped_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPrecision {
+    /// `[HH:MM:SS]`
+    #[default]
+    Seconds,
+    /// `[HH:MM:SS.mmm]`
+    Milliseconds,
 }
 
+/// Speaker key used for segments without a `speaker_id`.
+pub const UNKNOWN_SPEAKER: u32 = u32::MAX;
+
 /// Result of a transcription operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionResult {
@@ -53,8 +155,86 @@ pub struct TranscriptionResult {
     pub language: String,
     /// Processing time in milliseconds.
     pub processing_time_ms: u64,
+    /// Part of `processing_time_ms` spent resampling the input to 16kHz.
+    #[serde(default)]
+    pub resample_time_ms: u64,
+    /// Part of `processing_time_ms` spent decoding with Whisper.
+    #[serde(default)]
+    pub inference_time_ms: u64,
     /// Audio duration in milliseconds.
     pub audio_duration_ms: u64,
+    /// Duration of the audio actually decoded, after VAD trimming and preview
+    /// sampling, in milliseconds.
+    #[serde(default)]
+    pub processed_duration_ms: u64,
+    /// Whether segments were dropped after reaching `max_segments`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub segments_truncated: bool,
+    /// Whether this is a rough result from `transcribe_preview`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_preview: bool,
+    /// Whether the audio had speech-level energy (see `vad_threshold`).
+    ///
+    /// Empty text with this set means the model produced nothing for audible
+    /// input, rather than the input being silent or too short to transcribe.
+    #[serde(default)]
+    pub had_speech: bool,
+    /// Time ranges that could not be transcribed by the chunked path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_ranges: Vec<FailedRange>,
+    /// Decoder output including special tokens, when `keep_special_tokens` is set.
+    ///
+    /// Covers every decoded segment, including ones later dropped by
+    /// `min_confidence` or `max_segments`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_text: Option<String>,
+    /// Whether `transcribe_chunked` was cancelled; the result then holds only
+    /// the chunks finished before [`TranscriptionEngine::cancel`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Whether decoding stopped at the `max_processing_ms` budget; the result
+    /// then holds only the segments finished in time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Whether beam search decoded nothing and the result comes from the
+    /// `greedy_fallback` retry.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub greedy_fallback: bool,
+    /// Segment confidence averaged weighted by duration, as computed by
+    /// [`average_confidence`](Self::average_confidence) when the result was built.
+    #[serde(default)]
+    pub average_confidence: f32,
+}
+
+/// A stretch of audio whose transcription failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedRange {
+    /// Start time in milliseconds.
+    pub start_ms: i64,
+    /// End time in milliseconds.
+    pub end_ms: i64,
+    /// Error from the last attempt.
+    pub error: String,
+}
+
+/// How [`TranscriptionEngine::transcribe_chunked`] will split some audio,
+/// worked out before any inference.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptionPlan {
+    /// Start and end of each chunk in milliseconds, in order.
+    pub chunks: Vec<(i64, i64)>,
+    /// Duration of the audio in milliseconds.
+    pub audio_duration_ms: u64,
+    /// Expected processing time in milliseconds, from the engine's
+    /// realtime factor estimate.
+    pub estimated_time_ms: u64,
+}
+
+impl TranscriptionPlan {
+    /// Returns the number of chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
 }
 
 impl TranscriptionResult {
@@ -65,8 +245,69 @@ impl TranscriptionResult {
             segments: Vec::new(),
             language: String::new(),
             processing_time_ms: 0,
+            resample_time_ms: 0,
+            inference_time_ms: 0,
             audio_duration_ms: 0,
+            processed_duration_ms: 0,
+            segments_truncated: false,
+            is_preview: false,
+            had_speech: false,
+            failed_ranges: Vec::new(),
+            raw_text: None,
+            cancelled: false,
+            timed_out: false,
+            greedy_fallback: false,
+            average_confidence: 0.0,
+        }
+    }
+
+    /// Appends `other`, shifting its timestamps by `time_offset_ms`.
+    ///
+    /// Text is joined with a space, unless the kept language is written
+    /// without word spaces, and audio duration and processing time are summed
+    /// so [`realtime_factor`](Self::realtime_factor) covers both parts. The
+    /// language of the first result with text is kept.
+    pub fn append(&mut self, other: TranscriptionResult, time_offset_ms: i64) {
+        if self.text.is_empty() && !other.text.is_empty() {
+            self.language = other.language;
+        }
+        self.text = crate::text::join_segment_texts_with(
+            [self.text.as_str(), other.text.as_str()],
+            crate::text::uses_word_spaces(&self.language),
+        );
+        self.processing_time_ms += other.processing_time_ms;
+        self.resample_time_ms += other.resample_time_ms;
+        self.inference_time_ms += other.inference_time_ms;
+        self.audio_duration_ms += other.audio_duration_ms;
+        self.processed_duration_ms += other.processed_duration_ms;
+        self.had_speech |= other.had_speech;
+        self.is_preview |= other.is_preview;
+        self.segments_truncated |= other.segments_truncated;
+        self.cancelled |= other.cancelled;
+        self.timed_out |= other.timed_out;
+        self.greedy_fallback |= other.greedy_fallback;
+        if let Some(raw) = other.raw_text {
+            self.raw_text.get_or_insert_with(String::new).push_str(&raw);
         }
+
+        self.segments.extend(other.segments.into_iter().map(|mut segment| {
+            segment.shift(time_offset_ms);
+            segment
+        }));
+        self.failed_ranges.extend(other.failed_ranges.into_iter().map(|mut range| {
+            range.start_ms += time_offset_ms;
+            range.end_ms += time_offset_ms;
+            range
+        }));
+        self.average_confidence = self.average_confidence();
+    }
+
+    /// Returns the mean segment confidence, weighting each segment by its
+    /// duration, or 0.0 without segments.
+    ///
+    /// Segments are weighted equally if none has a duration.
+    pub fn average_confidence(&self) -> f32 {
+        average_confidence(&self.segments)
     }
 
     /// Returns the real-time factor (processing time / audio duration).
@@ -76,13 +317,371 @@ impl TranscriptionResult {
         }
         self.processing_time_ms as f64 / self.audio_duration_ms as f64
     }
+
+    /// Returns the real-time factor against the audio actually decoded
+    /// (processing time / processed duration).
+    ///
+    /// Unlike [`realtime_factor`](Self::realtime_factor), this is not
+    /// flattered by silence that VAD removed before decoding.
+    pub fn processed_realtime_factor(&self) -> f64 {
+        if self.processed_duration_ms == 0 {
+            return 0.0;
+        }
+        self.processing_time_ms as f64 / self.processed_duration_ms as f64
+    }
+
+    /// Returns the segments overlapping the half-open range `[start_ms, end_ms)`.
+    pub fn segments_in_range(&self, start_ms: i64, end_ms: i64) -> impl Iterator<Item = &Segment> {
+        self.segments
+            .iter()
+            .filter(move |s| s.start_ms < end_ms && s.end_ms > start_ms)
+    }
+
+    /// Returns the segment being spoken at `ms`.
+    ///
+    /// Segments cover `[start_ms, end_ms)`, so a timestamp on the boundary
+    /// between two segments belongs to the later one.
+    pub fn segment_at(&self, ms: i64) -> Option<&Segment> {
+        self.segments
+            .iter()
+            .find(|s| s.start_ms <= ms && ms < s.end_ms)
+    }
+
+    /// Returns the summed duration of all segments in milliseconds.
+    pub fn total_speech_ms(&self) -> i64 {
+        self.segments.iter().map(|s| s.duration_ms()).sum()
+    }
+
+    /// Groups segments by speaker, in time order within each speaker.
+    ///
+    /// Segments without a `speaker_id` are grouped under [`UNKNOWN_SPEAKER`].
+    pub fn by_speaker(&self) -> BTreeMap<u32, Vec<&Segment>> {
+        let mut groups: BTreeMap<u32, Vec<&Segment>> = BTreeMap::new();
+        for segment in &self.segments {
+            groups
+                .entry(segment.speaker_id.unwrap_or(UNKNOWN_SPEAKER))
+                .or_default()
+                .push(segment);
+        }
+        groups
+    }
+
+    /// Returns everything said by speaker `id`, joined with single spaces.
+    ///
+    /// Pass [`UNKNOWN_SPEAKER`] for segments without a speaker.
+    pub fn speaker_transcript(&self, id: u32) -> String {
+        crate::text::join_segment_texts(
+            self.segments
+                .iter()
+                .filter(|s| s.speaker_id.unwrap_or(UNKNOWN_SPEAKER) == id)
+                .map(|s| s.text.as_str()),
+        )
+    }
+
+    /// Returns the number of words across all segments.
+    ///
+    /// Words are whitespace-separated tokens containing at least one
+    /// alphanumeric character, so stray punctuation is not counted.
+    pub fn word_count(&self) -> usize {
+        self.segments
+            .iter()
+            .flat_map(|s| s.text.split_whitespace())
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count()
+    }
+
+    /// Returns the speaking rate in words per minute of speech.
+    ///
+    /// Based on [`total_speech_ms`](Self::total_speech_ms); returns 0.0 when
+    /// there is no speech.
+    pub fn words_per_minute(&self) -> f64 {
+        let speech_ms = self.total_speech_ms();
+        if speech_ms <= 0 {
+            return 0.0;
+        }
+        self.word_count() as f64 * 60_000.0 / speech_ms as f64
+    }
+
+    /// Redistributes the text into consecutive windows of `window_ms`,
+    /// starting at 0, leaving `segments` untouched.
+    ///
+    /// Each word goes to the window containing its midpoint, so a word
+    /// crossing a window boundary stays whole. Words are timed by their word
+    /// timestamps when the segment has them, and otherwise by interpolating
+    /// their character position across the segment. Windows without
+    /// words are omitted. A window's confidence is the lowest of its source
+    /// segments, and its speaker is kept only if they all agree.
+    pub fn resegment_fixed(&self, window_ms: i64) -> Vec<Segment> {
+        if window_ms <= 0 {
+            return self.segments.clone();
+        }
+
+        let mut windows: Vec<Segment> = Vec::new();
+        let end_limit = self.segments.iter().map(|s| s.end_ms).max().unwrap_or(0);
+
+        for segment in &self.segments {
+            for (word, time, timing) in timed_words(segment) {
+                let start_ms = time.div_euclid(window_ms) * window_ms;
+                match windows.last_mut() {
+                    Some(window) if window.start_ms == start_ms => {
+                        window.text.push(' ');
+                        window.text.push_str(word);
+                        window.confidence = window.confidence.min(segment.confidence);
+                        if window.speaker_id != segment.speaker_id {
+                            window.speaker_id = None;
+                        }
+                        window.words.extend(timing.cloned());
+                    }
+                    _ => {
+                        let end_ms = (start_ms + window_ms).min(end_limit.max(start_ms));
+                        let mut window = Segment::new(start_ms, end_ms, word.to_string());
+                        window.confidence = segment.confidence;
+                        window.speaker_id = segment.speaker_id;
+                        window.is_final = segment.is_final;
+                        window.words.extend(timing.cloned());
+                        windows.push(window);
+                    }
+                }
+            }
+        }
+        windows
+    }
+
+    /// Renders one line per segment prefixed with its start time, e.g.
+    /// `[00:01:23] Some text here`.
+    pub fn to_timestamped_text(&self, precision: TimestampPrecision) -> String {
+        let mut text = String::new();
+        for segment in &self.segments {
+            let ms = segment.start_ms.max(0);
+            let (hours, minutes, seconds) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+            let stamp = match precision {
+                TimestampPrecision::Seconds => format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+                TimestampPrecision::Milliseconds => {
+                    format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, ms % 1000)
+                }
+            };
+            text.push_str(&format!("[{}] {}\n", stamp, segment.text.trim()));
+        }
+        text
+    }
+
+    /// Exports the segments as CSV (RFC 4180).
+    ///
+    /// Columns: `start_ms`, `end_ms`, `duration_ms`, `confidence`, `avg_logprob`,
+    /// `speaker_id`, `text`.
+    /// `speaker_id` is empty when unknown.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("start_ms,end_ms,duration_ms,confidence,avg_logprob,speaker_id,text\r\n");
+
+        for segment in &self.segments {
+            let speaker = segment
+                .speaker_id
+                .map(|id| id.to_string())
+                .unwrap_or_default();
+
+            csv.push_str(&format!(
+                "{},{},{},{:.4},{:.4},{},{}\r\n",
+                segment.start_ms,
+                segment.end_ms,
+                segment.duration_ms(),
+                segment.confidence,
+                segment.avg_logprob,
+                speaker,
+                escape_csv_field(segment.text.trim())
+            ));
+        }
+
+        csv
+    }
+
+    /// Regroups the words of all segments into new segments of
+    /// `words_per_segment` words each, timed from their first and last word.
+    ///
+    /// Words of different speakers are never grouped together, so the last
+    /// segment of each speaker's turn may be shorter. Needs word timestamps
+    /// (see `word_timestamps`); segments without words are skipped. A
+    /// `words_per_segment` of 0 is treated as 1.
+    pub fn segment_by_words(&self, words_per_segment: usize) -> Vec<Segment> {
+        let words_per_segment = words_per_segment.max(1);
+        let mut regrouped: Vec<Segment> = Vec::new();
+
+        for source in &self.segments {
+            for word in &source.words {
+                let extend = regrouped.last().is_some_and(|last| {
+                    last.speaker_id == source.speaker_id && last.words.len() < words_per_segment
+                });
+                if !extend {
+                    let mut segment = Segment::new(word.start_ms, word.end_ms, String::new());
+                    segment.speaker_id = source.speaker_id;
+                    regrouped.push(segment);
+                }
+                regrouped.last_mut().unwrap().words.push(word.clone());
+            }
+        }
+
+        for segment in &mut regrouped {
+            let count = segment.words.len() as f32;
+            segment.end_ms = segment.words.iter().map(|w| w.end_ms).max().unwrap_or(segment.start_ms);
+            segment.text = segment.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+            segment.confidence = segment.words.iter().map(|w| w.probability).sum::<f32>() / count;
+            segment.avg_logprob =
+                segment.words.iter().map(|w| w.probability.max(f32::MIN_POSITIVE).ln()).sum::<f32>() / count;
+        }
+        regrouped
+    }
+
+    /// Concatenates the segments' exact decoder text, without trimming or
+    /// added separators, or returns `None` if any segment lacks `raw_text`
+    /// (see `keep_raw_segment_text`).
+    pub fn raw_segments_text(&self) -> Option<String> {
+        self.segments.iter().map(|s| s.raw_text.as_deref()).collect()
+    }
+
+    /// Renders the segments as SubRip (`.srt`) subtitles.
+    ///
+    /// Cues are numbered from 1; segments with no text are skipped.
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        let cues = self.segments.iter().filter(|s| !s.text.trim().is_empty());
+        for (index, segment) in cues.enumerate() {
+            srt.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                subtitle_timestamp(segment.start_ms, ','),
+                subtitle_timestamp(segment.end_ms, ','),
+                segment.text.trim()
+            ));
+        }
+        srt
+    }
+
+    /// Renders the segments as WebVTT (`.vtt`) subtitles.
+    ///
+    /// Segments with no text are skipped.
+    pub fn to_vtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        for segment in self.segments.iter().filter(|s| !s.text.trim().is_empty()) {
+            vtt.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                subtitle_timestamp(segment.start_ms, '.'),
+                subtitle_timestamp(segment.end_ms, '.'),
+                segment.text.trim()
+            ));
+        }
+        vtt
+    }
+}
+
+/// Formats `ms` as `HH:MM:SS` plus milliseconds after `separator`, as
+/// subtitle formats expect (SRT uses ',', WebVTT '.').
+fn subtitle_timestamp(ms: i64, separator: char) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// Quotes a CSV field if it contains separators, quotes, or line breaks.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
 }
 
 /// The main transcription engine.
 pub struct TranscriptionEngine {
     config: WhisperConfig,
-    ctx: Option<Arc<WhisperContext>>,
+    /// Loaded model, shared with the idle-unload watcher thread.
+    model: Arc<Mutex<ModelSlot>>,
+    /// Resolved path of the model, kept for transparent reloads.
+    model_path: String,
     is_initialized: bool,
+    /// Realtime factor measured from previous transcriptions.
+    measured_rtf: Mutex<Option<f64>>,
+    /// Set by `cancel`; checked between chunks of `transcribe_chunked`.
+    cancel_requested: AtomicBool,
+    /// Whether `last_result` is kept; see `set_keep_last_result`.
+    keep_last_result: AtomicBool,
+    /// Result of the last successful public transcription call.
+    last_result: Mutex<Option<TranscriptionResult>>,
+}
+
+/// The loaded context plus the bookkeeping needed to unload it when idle.
+struct ModelSlot {
+    ctx: Option<Arc<WhisperContext>>,
+    /// Models from `language_model_paths`, loaded on first use and unloaded
+    /// with `ctx`.
+    language_models: HashMap<String, Arc<WhisperContext>>,
+    /// How long the last model load took.
+    load_time: Option<Duration>,
+    last_used: Instant,
+    /// Unload after this much inactivity (`None` = keep resident).
+    idle_timeout: Option<Duration>,
+    watcher_running: bool,
+}
+
+impl ModelSlot {
+    fn new() -> Self {
+        Self {
+            ctx: None,
+            language_models: HashMap::new(),
+            load_time: None,
+            last_used: Instant::now(),
+            idle_timeout: None,
+            watcher_running: false,
+        }
+    }
+
+    /// Returns whether the model has gone unused past the idle timeout at `now`.
+    fn is_idle(&self, now: Instant) -> bool {
+        self.idle_timeout
+            .is_some_and(|timeout| now.saturating_duration_since(self.last_used) >= timeout)
+    }
+
+    /// One check of the idle watcher at `now`: unloads an idle model and
+    /// returns the wait before the next check, or `None` once idle
+    /// unloading is disabled.
+    fn idle_tick(&mut self, now: Instant) -> Option<Duration> {
+        let Some(timeout) = self.idle_timeout else {
+            self.watcher_running = false;
+            return None;
+        };
+
+        let loaded = self.ctx.is_some() || !self.language_models.is_empty();
+        if loaded && self.is_idle(now) {
+            tracing::info!("Unloading models after {}s idle", timeout.as_secs());
+            self.ctx = None;
+            self.language_models.clear();
+        }
+        Some((timeout / 4).clamp(Duration::from_millis(10), Duration::from_secs(1)))
+    }
+}
+
+/// Clears a cancel request when dropped, so it ends with the chunked
+/// transcription it stopped.
+struct CancelScope<'a>(&'a AtomicBool);
+
+impl Drop for CancelScope<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Marks the model as used again when dropped, so the idle timeout counts
+/// from the end of a decode rather than its start.
+struct ModelUse<'a>(&'a Mutex<ModelSlot>);
+
+impl Drop for ModelUse<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().last_used = Instant::now();
+    }
 }
 
 // Implement Send and Sync for thread safety
@@ -94,8 +693,13 @@ impl TranscriptionEngine {
     pub fn new(config: WhisperConfig) -> Self {
         Self {
             config,
-            ctx: None,
+            model: Arc::new(Mutex::new(ModelSlot::new())),
+            model_path: String::new(),
             is_initialized: false,
+            measured_rtf: Mutex::new(None),
+            cancel_requested: AtomicBool::new(false),
+            keep_last_result: AtomicBool::new(false),
+            last_result: Mutex::new(None),
         }
     }
 
@@ -117,134 +721,410 @@ impl TranscriptionEngine {
             return Err(WhisperError::ModelNotFound(model_path));
         }
 
-        tracing::info!("Loading Whisper model from: {}", model_path);
-
-        // Create context parameters
-        let params = WhisperContextParameters::default();
+        let mut slot = self.model.lock().unwrap();
+        load_model(&model_path, &self.config, &mut slot)?;
+        drop(slot);
 
-        // Load the model
-        let ctx = WhisperContext::new_with_params(&model_path, params)
-            .map_err(|e| WhisperError::ContextInitError(format!("Failed to load model: {}", e)))?;
-
-        self.ctx = Some(Arc::new(ctx));
+        self.model_path = model_path;
         self.is_initialized = true;
-        
-        tracing::info!("Whisper model loaded successfully");
         Ok(())
     }
 
+    /// Returns the loaded context, reloading it if it was unloaded while idle.
+    fn context(&self) -> Result<Arc<WhisperContext>> {
+        let mut slot = self.model.lock().unwrap();
+        slot.last_used = Instant::now();
+
+        if slot.ctx.is_none() {
+            tracing::info!("Reloading model unloaded after inactivity");
+            load_model(&self.model_path, &self.config, &mut slot)?;
+        }
+
+        slot.ctx
+            .clone()
+            .ok_or_else(|| WhisperError::ContextInitError("Context not available".to_string()))
+    }
+
+    /// Unloads the model, and any per-language models, after `timeout`
+    /// without transcriptions (`None` = never).
+    ///
+    /// The next transcription after an unload reloads the model first, so it
+    /// pays the load time again; an in-progress transcription keeps the model
+    /// alive until it finishes, and the timeout counts from then.
+    pub fn set_idle_unload(&self, timeout: Option<Duration>) {
+        let mut slot = self.model.lock().unwrap();
+        slot.idle_timeout = timeout;
+        slot.last_used = Instant::now();
+
+        if timeout.is_some() && !slot.watcher_running {
+            slot.watcher_running = true;
+            let model = Arc::downgrade(&self.model);
+            std::thread::spawn(move || watch_idle(model));
+        }
+    }
+
+    /// Returns the idle-unload timeout, if any.
+    pub fn idle_unload(&self) -> Option<Duration> {
+        self.model.lock().unwrap().idle_timeout
+    }
+
+    /// Returns whether the model is currently resident in memory.
+    pub fn is_model_loaded(&self) -> bool {
+        self.model.lock().unwrap().ctx.is_some()
+    }
+
     /// Returns the default model path for the configured model size.
     fn get_default_model_path(&self) -> Result<String> {
-        let home = std::env::var("HOME")
-            .map_err(|_| WhisperError::ConfigError("HOME not set".to_string()))?;
-        
-        let models_dir = format!(
-            "{}/Library/Application Support/BetterFasterWhisper/Models",
-            home
-        );
-
-        Ok(format!("{}/{}", models_dir, self.config.model_size.filename()))
+        let path = crate::config::quantized_model_path(
+            self.config.model_size,
+            self.config.quantization.as_deref(),
+        )?;
+        Ok(path.to_string_lossy().into_owned())
     }
 
     /// Transcribes audio from a buffer.
     pub fn transcribe(&self, audio: &AudioBuffer) -> Result<TranscriptionResult> {
+        self.transcribe_with(audio, &self.config.transcription_options())
+    }
+
+    /// Transcribes audio from a buffer, decoding with `options` instead of
+    /// the configured language, prompt, sampling and timestamp settings.
+    ///
+    /// Everything else, like VAD, audio conditioning and segment filtering,
+    /// still follows the engine's config.
+    pub fn transcribe_with(&self, audio: &AudioBuffer, options: &TranscriptionOptions) -> Result<TranscriptionResult> {
+        let deadline = self.processing_deadline();
+        let prompt = options.initial_prompt.as_deref();
+        self.remember(self.transcribe_impl(audio, self.config.vad_enabled, Effort::Full, options, prompt, deadline))
+    }
+
+    /// Keeps a copy of each successful transcription's result for
+    /// `last_result` and the segment accessors (off by default).
+    ///
+    /// Turning it off drops any stored result.
+    pub fn set_keep_last_result(&self, keep: bool) {
+        self.keep_last_result.store(keep, Ordering::SeqCst);
+        if !keep {
+            self.clear_last_result();
+        }
+    }
+
+    /// Returns a copy of the result of the last successful transcription
+    /// call, or `None` if there was none, it was cleared, or results are not
+    /// kept (see `set_keep_last_result`).
+    pub fn last_result(&self) -> Option<TranscriptionResult> {
+        self.last_result.lock().unwrap().clone()
+    }
+
+    /// Returns the number of segments in the last result, without copying it.
+    pub fn last_segment_count(&self) -> Option<usize> {
+        self.last_result.lock().unwrap().as_ref().map(|r| r.segments.len())
+    }
+
+    /// Returns a copy of segment `index` of the last result.
+    pub fn last_segment(&self, index: usize) -> Option<Segment> {
+        self.last_result.lock().unwrap().as_ref()?.segments.get(index).cloned()
+    }
+
+    /// Drops the stored last result, freeing its memory.
+    ///
+    /// Afterwards `last_result` and the segment accessors return `None` until
+    /// the next transcription.
+    pub fn clear_last_result(&self) {
+        *self.last_result.lock().unwrap() = None;
+    }
+
+    /// Stores a successful result as the last result, if results are kept,
+    /// and passes it through.
+    fn remember(&self, outcome: Result<TranscriptionResult>) -> Result<TranscriptionResult> {
+        if let (Ok(result), true) = (&outcome, self.keep_last_result.load(Ordering::SeqCst)) {
+            *self.last_result.lock().unwrap() = Some(result.clone());
+        }
+        outcome
+    }
+
+    /// Quickly produces rough text for deciding whether `audio` is worth a
+    /// full transcription.
+    ///
+    /// Decoding is greedy without temperature fallback, and audio longer than
+    /// two minutes is sampled as evenly spaced 10-second excerpts; segment
+    /// times still refer to the full audio. The result has `is_preview` set.
+    pub fn transcribe_preview(&self, audio: &AudioBuffer) -> Result<TranscriptionResult> {
+        validate_sample_rate(audio.sample_rate())?;
+
+        let rate = audio.sample_rate();
+        let samples_to_ms = |samples: usize| (samples as u64 * 1000 / rate as u64) as i64;
+        let mut result = TranscriptionResult::empty();
+        let options = self.config.transcription_options();
+        let deadline = self.processing_deadline();
+
+        for excerpt in preview_excerpts(audio.len(), rate) {
+            let start_ms = samples_to_ms(excerpt.start);
+            let excerpt = AudioBuffer::from_samples(audio.samples()[excerpt].to_vec(), rate);
+            let part =
+                self.transcribe_impl(&excerpt, self.config.vad_enabled, Effort::Preview, &options, self.prompt(), deadline)?;
+            result.append(part, start_ms);
+        }
+
+        result.audio_duration_ms = (audio.duration_seconds() * 1000.0) as u64;
+        result.is_preview = true;
+        self.remember(Ok(result))
+    }
+
+    /// Transcribes audio, optionally trimming silence first.
+    fn transcribe_impl(
+        &self,
+        audio: &AudioBuffer,
+        apply_vad: bool,
+        effort: Effort,
+        options: &TranscriptionOptions,
+        prompt: Option<&str>,
+        deadline: Option<Instant>,
+    ) -> Result<TranscriptionResult> {
         if !self.is_initialized {
             return Err(WhisperError::ContextInitError(
                 "Engine not initialized. Call initialize() first.".to_string(),
             ));
         }
 
+        validate_sample_rate(audio.sample_rate())?;
+
         if audio.is_empty() {
             return Ok(TranscriptionResult::empty());
         }
 
-        let ctx = self.ctx.as_ref()
-            .ok_or_else(|| WhisperError::ContextInitError("Context not available".to_string()))?;
-
         let start_time = std::time::Instant::now();
         let audio_duration_ms = (audio.duration_seconds() * 1000.0) as u64;
 
+        if audio_duration_ms < self.config.min_audio_ms as u64 {
+            tracing::debug!(
+                "Skipping {}ms of audio (minimum {}ms)",
+                audio_duration_ms,
+                self.config.min_audio_ms
+            );
+            return Ok(TranscriptionResult {
+                audio_duration_ms,
+                ..TranscriptionResult::empty()
+            });
+        }
+
+        // Kept for a greedy retry, which conditions it again itself
+        let input = audio;
+
+        // Condition at the input rate, before resampling narrows the band
+        let conditioned;
+        let audio = if self.config.speech_bandpass || self.config.compress_dynamics || self.config.auto_gain {
+            let mut copy = audio.clone();
+            if self.config.speech_bandpass {
+                copy.speech_bandpass();
+            }
+            if self.config.compress_dynamics {
+                copy.compress_dynamics(COMPRESSOR_THRESHOLD_DB, COMPRESSOR_RATIO);
+            }
+            if self.config.auto_gain {
+                apply_auto_gain(&mut copy);
+            }
+            conditioned = copy;
+            &conditioned
+        } else {
+            audio
+        };
+
         // Resample to 16kHz if necessary (Whisper requires 16kHz)
+        let resample_start = Instant::now();
         let samples = if audio.sample_rate() != 16000 {
-            resample_to_16khz(audio.samples(), audio.sample_rate())
+            resample_to_16khz(audio.samples(), audio.sample_rate(), self.config.resample_quality)?
         } else {
             audio.samples().to_vec()
         };
+        let resample_time_ms = resample_start.elapsed().as_millis() as u64;
 
-        // Create transcription parameters
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        // Cut long silences; the timeline maps decoded times back to the input
+        let speech = vad::detect_speech(&samples, WHISPER_SAMPLE_RATE, self.config.vad_threshold);
+        let had_speech = !speech.is_empty();
+        let (samples, timeline) = if apply_vad {
+            if !had_speech {
+                return Ok(TranscriptionResult {
+                    processing_time_ms: start_time.elapsed().as_millis() as u64,
+                    resample_time_ms,
+                    audio_duration_ms,
+                    ..TranscriptionResult::empty()
+                });
+            }
+            let timeline = vad::Timeline::new(&speech, WHISPER_SAMPLE_RATE);
+            (vad::retain(&samples, &speech), Some(timeline))
+        } else {
+            (samples, None)
+        };
+        let processed_duration_ms = samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64;
 
-        // Set language
-        if self.config.language.source != "auto" {
-            params.set_language(Some(&self.config.language.source));
-        }
+        let mut ctx = self.context()?;
+        let _in_use = ModelUse(&self.model);
 
-        // Configure parameters
-        params.set_translate(self.config.language.translate_to_english);
-        params.set_print_special(false);
-        params.set_print_progress(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(false);
-        params.set_suppress_blank(true);
-        params.set_suppress_non_speech_tokens(true);
+        // Create state for language detection and inference
+        let mut state = ctx.create_state()
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to create state: {}", e)))?;
 
-        // Set thread count
-        if self.config.n_threads > 0 {
-            params.set_n_threads(self.config.n_threads as i32);
+        // Restricted auto-detection, or a per-language model, needs the
+        // language up front; decoding then uses it
+        let restricted_language = if self.detects_language_up_front(&options.language.source) {
+            Some(self.detect_language_with_state(&mut state, &samples)?)
+        } else {
+            None
+        };
+
+        let model_language = restricted_language.as_deref().unwrap_or(&options.language.source);
+        if let Some(language_ctx) = self.language_context(model_language)? {
+            ctx = language_ctx;
+            state = ctx.create_state()
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to create state: {}", e)))?;
+        }
+        if !ctx.is_multilingual() {
+            check_english_only(model_language, options.language.translate_to_english)?;
         }
 
-        // Create state and run inference
-        let mut state = ctx.create_state()
-            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to create state: {}", e)))?;
+        // Must outlive every decode, which read them through the callbacks' user data
+        let suppressed = SuppressedTokens {
+            ids: self.config.suppress_tokens.clone(),
+            n_vocab: ctx.n_vocab(),
+        };
+        let deadline = Deadline::new(deadline);
+        let language = restricted_language
+            .as_deref()
+            .or(Some(options.language.source.as_str()).filter(|l| *l != "auto"));
+        let decode = DecodeOptions {
+            language,
+            prompt,
+            settings: options,
+            suppressed: &suppressed,
+            deadline: &deadline,
+        };
 
-        state.full(params, &samples)
-            .map_err(|e| WhisperError::TranscriptionError(format!("Transcription failed: {}", e)))?;
+        let parts = parallel_parts(samples.len(), self.config.n_processors);
+        let inference_start = Instant::now();
+        let DecodedPart { mut segments, raw_text, decoded } = if parts.len() > 1 {
+            tracing::debug!("Decoding in {} parallel parts", parts.len());
+            std::thread::scope(|scope| {
+                // The first part reuses `state`, which later reports the language
+                let workers: Vec<_> = parts[1..]
+                    .iter()
+                    .map(|part| {
+                        let ctx = &ctx;
+                        let samples = &samples[part.clone()];
+                        scope.spawn(move || {
+                            let mut state = ctx.create_state().map_err(|e| {
+                                WhisperError::TranscriptionError(format!("Failed to create state: {}", e))
+                            })?;
+                            self.decode_part(ctx, &mut state, samples, decode, effort)
+                        })
+                    })
+                    .collect();
 
-        // Extract results
-        let num_segments = state.full_n_segments()
-            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get segments: {}", e)))?;
+                let first = &samples[parts[0].clone()];
+                let mut decoded = self.decode_part(&ctx, &mut state, first, decode, effort)?;
+                for (part, worker) in parts[1..].iter().zip(workers) {
+                    let offset_ms = (part.start as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64;
+                    let part_decoded = worker.join().map_err(|_| {
+                        WhisperError::TranscriptionError("Parallel decode panicked".to_string())
+                    })??;
+                    if let (Some(raw), Some(part_raw)) = (decoded.raw_text.as_mut(), part_decoded.raw_text) {
+                        raw.push_str(&part_raw);
+                    }
+                    decoded.segments.extend(part_decoded.segments.into_iter().map(|mut segment| {
+                        segment.shift(offset_ms);
+                        segment
+                    }));
+                    decoded.decoded += part_decoded.decoded;
+                }
+                Ok::<_, WhisperError>(decoded)
+            })?
+        } else {
+            self.decode_part(&ctx, &mut state, &samples, decode, effort)?
+        };
+        let inference_time_ms = inference_start.elapsed().as_millis() as u64;
 
-        let mut segments = Vec::new();
-        let mut full_text = String::new();
+        if let Some(timeline) = timeline.as_ref().filter(|_| self.config.report_original_timestamps) {
+            for segment in &mut segments {
+                segment.start_ms = timeline.to_original_ms(segment.start_ms, false);
+                segment.end_ms = timeline.to_original_ms(segment.end_ms, true);
+                for word in &mut segment.words {
+                    word.start_ms = timeline.to_original_ms(word.start_ms, false);
+                    word.end_ms = timeline.to_original_ms(word.end_ms, true);
+                }
+            }
+        }
 
-        for i in 0..num_segments {
-            let segment_text = state.full_get_segment_text(i)
-                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get segment text: {}", e)))?;
-            
-            let start_timestamp = state.full_get_segment_t0(i)
-                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get start time: {}", e)))?;
-            
-            let end_timestamp = state.full_get_segment_t1(i)
-                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get end time: {}", e)))?;
+        let segments_truncated = self.config.max_segments > 0 && segments.len() > self.config.max_segments;
+        if segments_truncated {
+            tracing::warn!("Stopped after {} segments", self.config.max_segments);
+            segments.truncate(self.config.max_segments);
+        }
 
-            // Whisper timestamps are in centiseconds (1/100 of a second)
-            let start_ms = (start_timestamp as i64) * 10;
-            let end_ms = (end_timestamp as i64) * 10;
+        merge_short_segments(&mut segments, self.config.min_segment_ms);
+        if self.config.fix_overlaps {
+            fix_overlaps(&mut segments);
+        }
 
-            if !segment_text.trim().is_empty() {
-                full_text.push_str(&segment_text);
-                segments.push(Segment::new(start_ms, end_ms, segment_text));
+        // Segments dropped by the confidence or punctuation filters were still
+        // decoded, so only a decoder that produced nothing is retried
+        if had_speech && decoded == 0 {
+            let retry = effort == Effort::Full
+                && options.greedy_fallback
+                && options.beam_size > 1
+                && !deadline.timed_out.load(Ordering::SeqCst);
+            if retry {
+                tracing::warn!(
+                    "Beam search decoded nothing from {}ms of speech, retrying with greedy decoding",
+                    audio_duration_ms
+                );
+                drop(state);
+                let mut result = self.transcribe_impl(input, apply_vad, Effort::Greedy, options, prompt, deadline.at)?;
+                result.processing_time_ms = start_time.elapsed().as_millis() as u64;
+                result.greedy_fallback = true;
+                return Ok(result);
             }
+            tracing::warn!(
+                "No segments decoded from {}ms of audio with speech-level energy",
+                audio_duration_ms
+            );
         }
 
         // Detect language if auto
-        let language = if self.config.language.source == "auto" {
+        let language = if let Some(language) = restricted_language {
+            language
+        } else if options.language.source == "auto" {
             // Try to detect language from the state or default to "en"
             state.full_lang_id_from_state()
                 .map(|id| whisper_rs::get_lang_str(id).unwrap_or("en").to_string())
                 .unwrap_or_else(|_| "en".to_string())
         } else {
-            self.config.language.source.clone()
+            options.language.source.clone()
         };
 
+        // Build the full text from the kept segments only
+        let full_text = self.transcript_text(&segments, &language);
+        let average_confidence = average_confidence(&segments);
+
         let processing_time_ms = start_time.elapsed().as_millis() as u64;
 
         let result = TranscriptionResult {
-            text: full_text.trim().to_string(),
+            text: full_text,
             segments,
             language,
             processing_time_ms,
+            resample_time_ms,
+            inference_time_ms,
             audio_duration_ms,
+            processed_duration_ms,
+            segments_truncated,
+            is_preview: effort == Effort::Preview,
+            had_speech,
+            failed_ranges: Vec::new(),
+            raw_text,
+            cancelled: false,
+            timed_out: deadline.timed_out.load(Ordering::SeqCst),
+            greedy_fallback: false,
+            average_confidence,
         };
 
         tracing::info!(
@@ -254,71 +1134,1219 @@ impl TranscriptionEngine {
             result.realtime_factor()
         );
 
-        Ok(result)
-    }
+        // Preview decodes are cheaper and would skew time estimates
+        if effort == Effort::Full {
+            self.record_realtime_factor(&result);
+        }
 
-    /// Transcribes audio from a file.
-    pub fn transcribe_file(&self, path: &str) -> Result<TranscriptionResult> {
-        let audio = crate::audio::load_wav_file(path)?;
-        self.transcribe(&audio)
+        Ok(result)
     }
 
-    /// Returns whether the engine is initialized.
-    pub fn is_initialized(&self) -> bool {
-        self.is_initialized
-    }
+    /// Decodes 16kHz `samples` with `state`, returning the kept segments with
+    /// times relative to the start of `samples`.
+    fn decode_part(
+        &self,
+        ctx: &WhisperContext,
+        state: &mut WhisperState,
+        samples: &[f32],
+        options: DecodeOptions,
+        effort: Effort,
+    ) -> Result<DecodedPart> {
+        // Create transcription parameters
+        let strategy = match effort {
+            Effort::Full => build_sampling_strategy(options.settings),
+            Effort::Preview => SamplingStrategy::Greedy { best_of: 1 },
+            Effort::Greedy => greedy_strategy(options.settings),
+        };
+        let mut params = FullParams::new(strategy);
+        if options.language.is_some() {
+            params.set_language(options.language);
+        }
+        apply_text_context(&mut params, options.settings.condition_on_previous_text, options.prompt);
 
-    /// Returns a reference to the current configuration.
-    pub fn config(&self) -> &WhisperConfig {
-        &self.config
-    }
+        // Configure parameters
+        params.set_translate(options.settings.language.translate_to_english);
+        params.set_temperature(options.settings.temperature);
+        params.set_temperature_inc(match effort {
+            Effort::Full | Effort::Greedy => options.settings.temperature_increment,
+            Effort::Preview => 0.0,
+        });
+        params.set_print_special(false);
+        params.set_print_progress(self.config.verbose);
+        params.set_print_realtime(self.config.verbose);
+        params.set_print_timestamps(false);
+        params.set_suppress_blank(true);
+        params.set_suppress_non_speech_tokens(true);
+        params.set_logprob_thold(self.config.logprob_threshold);
+        params.set_entropy_thold(self.config.entropy_threshold);
+        params.set_token_timestamps(options.settings.word_timestamps);
 
-    /// Updates the configuration (requires re-initialization).
-    pub fn set_config(&mut self, config: WhisperConfig) {
-        self.config = config;
-        self.is_initialized = false;
-        self.ctx = None;
-    }
+        // Split long segments at word boundaries so multibyte characters stay intact
+        if self.config.max_segment_length > 0 {
+            params.set_max_len(self.config.max_segment_length as i32);
+            params.set_split_on_word(true);
+        }
 
-    /// Releases resources and unloads the model.
-    pub fn shutdown(&mut self) {
-        self.ctx = None;
+        // Set thread count
+        if self.config.n_threads > 0 {
+            params.set_n_threads(self.config.n_threads as i32);
+        }
+
+        if !options.suppressed.ids.is_empty() {
+            // SAFETY: the callback only writes within the `n_vocab` logits, and
+            // `suppressed` is borrowed for the whole decode below.
+            unsafe {
+                params.set_filter_logits_callback(Some(suppress_tokens_callback));
+                params.set_filter_logits_callback_user_data(options.suppressed as *const _ as *mut c_void);
+            }
+        }
+
+        if options.deadline.at.is_some() {
+            // SAFETY: the callback only reads the deadline, which is borrowed
+            // for the whole decode below.
+            unsafe {
+                params.set_abort_callback(Some(deadline_abort_callback));
+                params.set_abort_callback_user_data(options.deadline as *const _ as *mut c_void);
+            }
+        }
+
+        // Run inference; after a timeout, the windows decoded in time are kept
+        if let Err(e) = state.full(params, samples) {
+            if !options.deadline.timed_out.load(Ordering::SeqCst) {
+                return Err(WhisperError::TranscriptionError(format!("Transcription failed: {}", e)));
+            }
+            tracing::warn!("Decoding stopped at the {}ms processing budget", self.config.max_processing_ms);
+        }
+
+        // Extract results
+        let num_segments = state.full_n_segments()
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get segments: {}", e)))?;
+
+        let mut segments = Vec::new();
+        let mut raw_text = self.config.keep_special_tokens.then(String::new);
+
+        for i in 0..num_segments {
+            // One segment past the limit tells the caller it was exceeded;
+            // the raw text still covers the rest
+            let past_limit = self.config.max_segments > 0 && segments.len() > self.config.max_segments;
+            if past_limit && raw_text.is_none() {
+                break;
+            }
+
+            // Read once for both the raw text and the segment's tokens
+            let tokens = if raw_text.is_some() || self.config.include_tokens {
+                segment_tokens(state, i)?
+            } else {
+                Vec::new()
+            };
+            if let Some(raw) = raw_text.as_mut() {
+                for token in &tokens {
+                    raw.push_str(&token.text);
+                }
+            }
+            if past_limit {
+                continue;
+            }
+
+            // Lossy decoding: a token split can still leave a partial UTF-8 sequence
+            let segment_text = state.full_get_segment_text_lossy(i)
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get segment text: {}", e)))?;
+            
+            let start_timestamp = state.full_get_segment_t0(i)
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get start time: {}", e)))?;
+            
+            let end_timestamp = state.full_get_segment_t1(i)
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get end time: {}", e)))?;
+
+            // Whisper timestamps are in centiseconds (1/100 of a second)
+            let start_ms = start_timestamp * 10;
+            let mut end_ms = end_timestamp * 10;
+
+            // Token timestamp glitches can put the end first; a negative
+            // duration would make invalid subtitle cues
+            if end_ms < start_ms {
+                tracing::warn!("Segment {} ends before it starts ({}ms < {}ms), clamping", i, end_ms, start_ms);
+                end_ms = start_ms;
+            }
+
+            if segment_text.trim().is_empty() {
+                continue;
+            }
+
+            if self.config.drop_punctuation_segments && !crate::text::has_word_characters(&segment_text) {
+                tracing::debug!("Dropping punctuation-only segment {}: {}", i, segment_text.trim());
+                continue;
+            }
+
+            let (confidence, avg_logprob) = segment_scores(state, ctx, i)?;
+            if confidence < self.config.min_confidence {
+                tracing::debug!(
+                    "Dropping segment {} with confidence {:.2}: {}",
+                    i,
+                    confidence,
+                    segment_text.trim()
+                );
+                continue;
+            }
+
+            let raw_segment_text = self.config.keep_raw_segment_text.then(|| segment_text.clone());
+            let segment_text = if self.config.trim_segment_whitespace {
+                segment_text.trim().to_string()
+            } else {
+                segment_text
+            };
+
+            let mut segment = Segment::new(start_ms, end_ms, segment_text);
+            segment.raw_text = raw_segment_text;
+            segment.confidence = confidence;
+            segment.avg_logprob = avg_logprob;
+            if self.config.include_tokens {
+                segment.tokens = tokens;
+            }
+            if options.settings.word_timestamps {
+                segment.words = segment_words(state, ctx, i)?;
+            }
+            segments.push(segment);
+        }
+
+        Ok(DecodedPart {
+            segments,
+            raw_text,
+            decoded: num_segments.max(0) as usize,
+        })
+    }
+
+    /// Detects the spoken language of `audio` from its first 30 seconds.
+    ///
+    /// When `detection_languages` is set, the most probable language among
+    /// them is returned; otherwise any language Whisper knows may be returned.
+    pub fn detect_language(&self, audio: &AudioBuffer) -> Result<String> {
+        if !self.is_initialized {
+            return Err(WhisperError::ContextInitError(
+                "Engine not initialized. Call initialize() first.".to_string(),
+            ));
+        }
+
+        let audio = audio.resample_with(WHISPER_SAMPLE_RATE, self.config.resample_quality)?;
+        let ctx = self.context()?;
+        let mut state = ctx.create_state()
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to create state: {}", e)))?;
+        self.detect_language_with_state(&mut state, audio.samples())
+    }
+
+    /// Returns whether decoding in `language` needs it detected first, either
+    /// to restrict auto-detection to `detection_languages` or to pick a model
+    /// from `language_model_paths`.
+    fn detects_language_up_front(&self, language: &str) -> bool {
+        language == "auto"
+            && (!self.config.detection_languages.is_empty() || !self.config.language_model_paths.is_empty())
+    }
+
+    /// Returns the model configured for `language` in `language_model_paths`,
+    /// loading it on first use, or `None` to use the default model.
+    fn language_context(&self, language: &str) -> Result<Option<Arc<WhisperContext>>> {
+        let Some(path) = self.config.language_model_paths.get(language) else {
+            return Ok(None);
+        };
+
+        if let Some(ctx) = self.model.lock().unwrap().language_models.get(language) {
+            return Ok(Some(ctx.clone()));
+        }
+
+        // Loaded without the lock, which other lookups and the idle watcher
+        // need meanwhile; a model loaded concurrently for the same language wins
+        if !Path::new(path).exists() {
+            return Err(WhisperError::ModelNotFound(path.clone()));
+        }
+        tracing::info!("Loading {} model", language);
+        let ctx = Arc::new(open_context(path, &self.config)?);
+        let mut slot = self.model.lock().unwrap();
+        slot.last_used = Instant::now();
+        Ok(Some(slot.language_models.entry(language.to_string()).or_insert(ctx).clone()))
+    }
+
+    /// Runs language detection on 16kHz `samples`, honouring `detection_languages`.
+    fn detect_language_with_state(&self, state: &mut WhisperState, samples: &[f32]) -> Result<String> {
+        let threads = match self.config.n_threads {
+            0 => std::thread::available_parallelism().map_or(4, |n| n.get().min(8)),
+            n => n as usize,
+        };
+        let window = samples.len().min(Self::DETECTION_SECONDS * WHISPER_SAMPLE_RATE as usize);
+
+        state.pcm_to_mel(&samples[..window], threads)
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to compute mel spectrogram: {}", e)))?;
+        let (_, probs) = state.lang_detect(0, threads)
+            .map_err(|e| WhisperError::TranscriptionError(format!("Language detection failed: {}", e)))?;
+
+        let id = pick_language(&probs, &self.config.detection_languages)
+            .ok_or_else(|| WhisperError::TranscriptionError("Language detection failed".to_string()))?;
+        Ok(whisper_rs::get_lang_str(id).unwrap_or("en").to_string())
+    }
+
+    /// Audio analysed by [`detect_language`](Self::detect_language).
+    const DETECTION_SECONDS: usize = 30;
+
+    /// Duration of the synthetic audio used by [`calibrate`](Self::calibrate).
+    const CALIBRATION_SECONDS: usize = 30;
+
+    /// Weight of the newest measurement in the running realtime factor.
+    const RTF_SMOOTHING: f64 = 0.3;
+
+    /// Runs a calibration transcription and returns the measured realtime factor.
+    ///
+    /// A full 30-second window of silence is transcribed so the measurement
+    /// reflects whisper's per-window cost.
+    pub fn calibrate(&self) -> Result<f64> {
+        let samples = vec![0.0; Self::CALIBRATION_SECONDS * WHISPER_SAMPLE_RATE as usize];
+        let audio = AudioBuffer::from_samples(samples, WHISPER_SAMPLE_RATE);
+        // Bypass VAD, which would discard the silence without decoding it
+        let options = self.config.transcription_options();
+        let result = self.transcribe_impl(&audio, false, Effort::Full, &options, self.prompt(), None)?;
+        Ok(result.realtime_factor())
+    }
+
+    /// Estimates how long transcribing `audio` will take, without running the model.
+    ///
+    /// Uses the realtime factor measured by previous transcriptions (or
+    /// [`calibrate`](Self::calibrate)), falling back to a benchmark figure for
+    /// the configured model size.
+    pub fn estimate_time(&self, audio: &AudioBuffer) -> Duration {
+        let rtf = self.realtime_factor_estimate();
+        Duration::from_secs_f64(audio.duration_seconds() as f64 * rtf)
+    }
+
+    /// Plans how [`transcribe_chunked`](Self::transcribe_chunked) with
+    /// `chunk_seconds` 0 will split `audio` for a standard 30-second-context
+    /// model, without loading or running the model.
+    pub fn plan(&self, audio: &AudioBuffer) -> TranscriptionPlan {
+        let chunk_seconds = context_window_seconds(STANDARD_AUDIO_CONTEXT);
+        let samples = match audio.sample_rate() {
+            0 => 0,
+            rate => (audio.len() as u64 * WHISPER_SAMPLE_RATE as u64 / rate as u64) as usize,
+        };
+        TranscriptionPlan {
+            chunks: chunk_ranges_ms(samples, chunk_seconds as usize * WHISPER_SAMPLE_RATE as usize),
+            audio_duration_ms: samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64,
+            estimated_time_ms: self.estimate_time(audio).as_millis() as u64,
+        }
+    }
+
+    /// Returns the realtime factor used for time estimates.
+    pub fn realtime_factor_estimate(&self) -> f64 {
+        self.measured_rtf
+            .lock()
+            .unwrap()
+            .unwrap_or_else(|| self.config.model_size.benchmark_realtime_factor())
+    }
+
+    /// Folds a result's realtime factor into the running measurement.
+    fn record_realtime_factor(&self, result: &TranscriptionResult) {
+        if result.audio_duration_ms == 0 {
+            return;
+        }
+        let rtf = result.realtime_factor();
+        let mut measured = self.measured_rtf.lock().unwrap();
+        *measured = Some(match *measured {
+            Some(prev) => prev + Self::RTF_SMOOTHING * (rtf - prev),
+            None => rtf,
+        });
+    }
+
+    /// Transcribes audio from a file; the path need not be valid UTF-8.
+    ///
+    /// The file is resampled with the configured `resample_quality`, counted
+    /// in `resample_time_ms`.
+    pub fn transcribe_file(&self, path: impl AsRef<Path>) -> Result<TranscriptionResult> {
+        let audio = crate::audio::load_audio_file(path)?;
+        self.transcribe(&audio)
+    }
+
+    /// Transcribes multi-channel audio, treating each channel as a separate speaker.
+    ///
+    /// Each channel is transcribed independently and its segments are tagged with
+    /// `speaker_id` equal to the channel index. Segments from all channels are
+    /// merged and sorted by start time.
+    pub fn transcribe_multichannel(&self, channels: &[AudioBuffer]) -> Result<TranscriptionResult> {
+        let options = self.config.transcription_options();
+        let deadline = self.processing_deadline();
+        let results = channels
+            .iter()
+            .map(|channel| {
+                self.transcribe_impl(channel, self.config.vad_enabled, Effort::Full, &options, self.prompt(), deadline)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut merged = merge_channel_results(results);
+        merged.text = self.transcript_text(&merged.segments, &merged.language);
+        self.remember(Ok(merged))
+    }
+
+    /// Joins segments into the full transcript, with or without spaces as
+    /// `join_with_spaces` and `language` dictate.
+    pub fn transcript_text(&self, segments: &[Segment], language: &str) -> String {
+        let with_spaces = self
+            .config
+            .join_with_spaces
+            .unwrap_or_else(|| crate::text::uses_word_spaces(language));
+        segments_text_with(segments, with_spaces)
+    }
+
+    /// Returns the loaded model's audio context length in encoder positions
+    /// (1500 for standard Whisper models, i.e. 30 seconds).
+    ///
+    /// Loads the model again if it was unloaded after being idle.
+    pub fn max_audio_context(&self) -> Result<u32> {
+        if !self.is_initialized {
+            return Err(WhisperError::ContextInitError(
+                "Engine not initialized. Call initialize() first.".to_string(),
+            ));
+        }
+        Ok(self.context()?.model_n_audio_ctx().max(0) as u32)
+    }
+
+    /// Transcribes long audio in independent windows of `chunk_seconds`.
+    ///
+    /// Pass 0 to use the longest window the loaded model decodes at once
+    /// (see [`max_audio_context`](Self::max_audio_context)). A window that fails is retried once with [`fallback_config`]; if that
+    /// also fails, its time range is covered by an [`UNINTELLIGIBLE_MARKER`]
+    /// segment and listed in `failed_ranges`, and the remaining windows are
+    /// still transcribed.
+    ///
+    /// After [`cancel`](Self::cancel), the windows finished so far are
+    /// returned with `cancelled` set, and `audio_duration_ms` covers only them.
+    /// The `max_processing_ms` budget covers all windows together; once it
+    /// runs out, the result likewise ends early with `timed_out` set.
+    pub fn transcribe_chunked(&self, audio: &AudioBuffer, chunk_seconds: u32) -> Result<TranscriptionResult> {
+        let chunk_seconds = self.chunk_seconds(chunk_seconds)?;
+
+        // Resampled once up front, which each chunk's own timing would miss
+        let resample_start = Instant::now();
+        let audio = audio.resample_with(WHISPER_SAMPLE_RATE, self.config.resample_quality)?;
+        let resample_time = resample_start.elapsed();
+
+        let chunk_len = chunk_seconds as usize * WHISPER_SAMPLE_RATE as usize;
+        let chunks = audio
+            .samples()
+            .chunks(chunk_len)
+            .map(|chunk| Ok(AudioBuffer::from_samples(chunk.to_vec(), WHISPER_SAMPLE_RATE)));
+        let result = self.transcribe_chunks(chunks)?;
+        self.finish_chunked(result, resample_time)
+    }
+
+    /// Transcribes a WAV file like [`transcribe_chunked`](Self::transcribe_chunked),
+    /// reading one window at a time with [`stream_wav_chunks`](crate::audio::stream_wav_chunks)
+    /// so memory stays bounded however large the file is.
+    ///
+    /// A read error part way through fails the whole call.
+    pub fn transcribe_wav_chunked(&self, path: impl AsRef<Path>, chunk_seconds: u32) -> Result<TranscriptionResult> {
+        let chunk_seconds = self.chunk_seconds(chunk_seconds)?;
+        let mut chunks =
+            crate::audio::stream_wav_chunks(path, chunk_seconds as f32, self.config.resample_quality)?;
+        let result = self.transcribe_chunks(chunks.by_ref())?;
+        self.finish_chunked(result, chunks.resample_time())
+    }
+
+    /// Resolves a `chunk_seconds` argument, where 0 means the longest window
+    /// the loaded model decodes at once.
+    fn chunk_seconds(&self, chunk_seconds: u32) -> Result<u32> {
+        let chunk_seconds = match chunk_seconds {
+            0 => context_window_seconds(self.max_audio_context()?),
+            seconds => seconds,
+        };
+        if chunk_seconds == 0 {
+            return Err(WhisperError::ConfigError("Model reports no audio context".to_string()));
+        }
+        Ok(chunk_seconds)
+    }
+
+    /// Transcribes consecutive 16kHz `chunks` as independent windows and
+    /// stitches them into one result.
+    fn transcribe_chunks(&self, chunks: impl Iterator<Item = Result<AudioBuffer>>) -> Result<TranscriptionResult> {
+        let samples_to_ms = |samples: usize| (samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64;
+
+        let mut result = TranscriptionResult::empty();
+        let mut fallback = None;
+        // Samples before the current chunk, then the samples covered
+        let mut offset = 0;
+        // Text of the previous chunk, prompted into the next one
+        let mut context = String::new();
+        let options = self.config.transcription_options();
+        let deadline = self.processing_deadline();
+        let _cancel_scope = CancelScope(&self.cancel_requested);
+
+        for chunk in chunks {
+            let chunk = chunk?;
+            let start_ms = samples_to_ms(offset);
+            let end_ms = samples_to_ms(offset + chunk.len());
+            if self.cancel_requested.load(Ordering::SeqCst) {
+                tracing::info!("Chunked transcription cancelled at {}ms", start_ms);
+                result.cancelled = true;
+                break;
+            }
+            if deadline.is_some_and(|at| Instant::now() >= at) {
+                tracing::warn!("Chunked transcription timed out at {}ms", start_ms);
+                result.timed_out = true;
+                break;
+            }
+            offset += chunk.len();
+
+            let prompt = self.prompt().or_else(|| {
+                (self.config.carry_context && self.config.condition_on_previous_text)
+                    .then(|| context_tail(&context, CARRIED_CONTEXT_CHARS))
+            });
+            let outcome = self
+                .transcribe_impl(&chunk, self.config.vad_enabled, Effort::Full, &options, prompt, deadline)
+                .or_else(|e| {
+                    tracing::warn!("Chunk at {}ms failed ({}), retrying with fallback config", start_ms, e);
+                    self.transcribe_with_fallback(&chunk, &mut fallback)
+                });
+            context = outcome.as_ref().map(|chunk| chunk.text.clone()).unwrap_or_default();
+            stitch_chunk(&mut result, outcome, start_ms, end_ms);
+            if result.timed_out {
+                break;
+            }
+        }
+
+        result.audio_duration_ms = samples_to_ms(offset) as u64;
+        result.text = self.transcript_text(&result.segments, &result.language);
+        Ok(result)
+    }
+
+    /// Adds the up-front resampling time to a chunked result and stores it.
+    fn finish_chunked(&self, mut result: TranscriptionResult, resample_time: Duration) -> Result<TranscriptionResult> {
+        let resample_time_ms = resample_time.as_millis() as u64;
+        result.resample_time_ms += resample_time_ms;
+        result.processing_time_ms += resample_time_ms;
+        self.remember(Ok(result))
+    }
+
+    /// Returns when a transcription starting now must stop, per `max_processing_ms`.
+    fn processing_deadline(&self) -> Option<Instant> {
+        (self.config.max_processing_ms > 0)
+            .then(|| Instant::now() + Duration::from_millis(self.config.max_processing_ms))
+    }
+
+    /// The configured initial prompt, if any.
+    fn prompt(&self) -> Option<&str> {
+        self.config.initial_prompt.as_deref()
+    }
+
+    /// Asks a running [`transcribe_chunked`](Self::transcribe_chunked) to stop
+    /// before its next chunk and return the chunks finished so far.
+    ///
+    /// Can be called from another thread, also just before the transcription
+    /// starts. The request is cleared when that transcription returns.
+    pub fn cancel(&self) {
+        self.cancel_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Transcribes with a lazily created engine using [`fallback_config`].
+    fn transcribe_with_fallback(
+        &self,
+        audio: &AudioBuffer,
+        fallback: &mut Option<TranscriptionEngine>,
+    ) -> Result<TranscriptionResult> {
+        let engine = match fallback {
+            Some(engine) => engine,
+            None => {
+                let config = fallback_config(&self.config);
+                let engine = if same_backend(&config, &self.config) {
+                    self.sharing_model(config)?
+                } else {
+                    let mut engine = TranscriptionEngine::new(config);
+                    engine.initialize()?;
+                    engine
+                };
+                fallback.insert(engine)
+            }
+        };
+        engine.transcribe(audio)
+    }
+
+    /// Creates an initialized engine for `config` that uses this engine's
+    /// loaded model instead of loading another copy.
+    fn sharing_model(&self, config: WhisperConfig) -> Result<TranscriptionEngine> {
+        let mut engine = TranscriptionEngine::new(config);
+        let ctx = self.context()?;
+        {
+            let mut slot = engine.model.lock().unwrap();
+            slot.ctx = Some(ctx);
+            slot.load_time = self.model.lock().unwrap().load_time;
+        }
+        engine.model_path = self.model_path.clone();
+        engine.is_initialized = true;
+        Ok(engine)
+    }
+
+    /// Returns how long the last successful model load took, in milliseconds.
+    ///
+    /// `None` until [`initialize`](Self::initialize) has succeeded.
+    pub fn model_load_time_ms(&self) -> Option<u64> {
+        self.model.lock().unwrap().load_time.map(|t| t.as_millis() as u64)
+    }
+
+    /// Returns whether the engine is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    /// Returns whether the loaded model supports languages other than English
+    /// (false for `.en` models, or if the engine is not initialized).
+    ///
+    /// Loads the model again if it was unloaded after being idle.
+    pub fn is_multilingual(&self) -> bool {
+        self.is_initialized && self.context().is_ok_and(|ctx| ctx.is_multilingual())
+    }
+
+    /// Returns a reference to the current configuration.
+    pub fn config(&self) -> &WhisperConfig {
+        &self.config
+    }
+
+    /// Updates the configuration (requires re-initialization).
+    pub fn set_config(&mut self, config: WhisperConfig) {
+        self.config = config;
+        self.is_initialized = false;
+        self.model.lock().unwrap().ctx = None;
+        *self.measured_rtf.lock().unwrap() = None;
+    }
+
+    /// Releases resources and unloads the model.
+    pub fn shutdown(&mut self) {
+        {
+            let mut slot = self.model.lock().unwrap();
+            slot.ctx = None;
+            slot.language_models.clear();
+        }
+        self.clear_last_result();
         self.is_initialized = false;
         tracing::info!("Whisper engine shut down");
     }
 }
 
-impl Drop for TranscriptionEngine {
-    fn drop(&mut self) {
-        self.shutdown();
+impl Drop for TranscriptionEngine {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Returns each word of `segment` with the time it is placed at, plus its
+/// word timestamp if the segment has them.
+///
+/// Without word timestamps, words are timed by interpolating their
+/// character position across the segment.
+fn timed_words(segment: &Segment) -> Vec<(&str, i64, Option<&Word>)> {
+    if !segment.words.is_empty() {
+        return segment
+            .words
+            .iter()
+            .map(|word| (word.text.trim(), (word.start_ms + word.end_ms) / 2, Some(word)))
+            .filter(|(text, _, _)| !text.is_empty())
+            .collect();
+    }
+
+    let text = segment.text.trim();
+    let total_chars = text.chars().count().max(1) as i64;
+    let mut char_offset = 0;
+    let mut words = Vec::new();
+    for word in text.split(char::is_whitespace) {
+        let word_chars = word.chars().count() as i64;
+        let center = char_offset + word_chars / 2;
+        char_offset += word_chars + 1;
+        if !word.is_empty() {
+            words.push((word, segment.start_ms + segment.duration_ms() * center / total_chars, None));
+        }
+    }
+    words
+}
+
+/// Duration-weighted mean confidence of `segments`; see
+/// [`TranscriptionResult::average_confidence`].
+fn average_confidence(segments: &[Segment]) -> f32 {
+    if segments.is_empty() {
+        return 0.0;
+    }
+
+    let total_ms: i64 = segments.iter().map(|s| s.duration_ms().max(0)).sum();
+    if total_ms == 0 {
+        return segments.iter().map(|s| s.confidence).sum::<f32>() / segments.len() as f32;
+    }
+    let weighted: f64 = segments
+        .iter()
+        .map(|s| s.confidence as f64 * s.duration_ms().max(0) as f64)
+        .sum();
+    (weighted / total_ms as f64) as f32
+}
+
+/// Builds the full transcript from segments, joined with single spaces.
+pub fn segments_text(segments: &[Segment]) -> String {
+    segments_text_with(segments, true)
+}
+
+/// Builds the full transcript from segments, joined with single spaces or,
+/// if `with_spaces` is false, directly.
+pub fn segments_text_with(segments: &[Segment], with_spaces: bool) -> String {
+    crate::text::join_segment_texts_with(segments.iter().map(|s| s.text.as_str()), with_spaces)
+}
+
+/// Audio covered by one encoder position: two 10ms mel frames.
+const AUDIO_CONTEXT_POSITION_MS: u32 = 20;
+
+/// Converts an audio context length to whole seconds of audio.
+fn context_window_seconds(n_audio_ctx: u32) -> u32 {
+    n_audio_ctx * AUDIO_CONTEXT_POSITION_MS / 1000
+}
+
+/// Audio context length of every released Whisper model.
+const STANDARD_AUDIO_CONTEXT: u32 = 1500;
+
+/// Splits `total` 16kHz samples into chunks of `chunk_len`, returning each
+/// chunk's start and end in milliseconds.
+fn chunk_ranges_ms(total: usize, chunk_len: usize) -> Vec<(i64, i64)> {
+    let samples_to_ms = |samples: usize| (samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64;
+    (0..total)
+        .step_by(chunk_len.max(1))
+        .map(|start| (samples_to_ms(start), samples_to_ms((start + chunk_len).min(total))))
+        .collect()
+}
+
+/// How a transcription trades accuracy for speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Effort {
+    /// Decode as configured.
+    Full,
+    /// Greedy decoding without temperature fallback.
+    Preview,
+    /// Greedy decoding, otherwise as configured.
+    Greedy,
+}
+
+/// Settings shared by every part of one decode.
+#[derive(Clone, Copy)]
+struct DecodeOptions<'a> {
+    /// Language to decode in, or `None` to detect it.
+    language: Option<&'a str>,
+    /// Text given to the decoder as preceding context.
+    prompt: Option<&'a str>,
+    /// The call's decoding options.
+    settings: &'a TranscriptionOptions,
+    /// Tokens masked out by the logits filter.
+    suppressed: &'a SuppressedTokens,
+    /// Time limit enforced by the abort callback.
+    deadline: &'a Deadline,
+}
+
+/// What one [`TranscriptionEngine::decode_part`] call produced.
+struct DecodedPart {
+    /// Segments kept after filtering, timed from the start of the part.
+    segments: Vec<Segment>,
+    /// Raw text with special tokens, if `keep_special_tokens` is set.
+    raw_text: Option<String>,
+    /// Segments the decoder produced, including those filtered out.
+    decoded: usize,
+}
+
+/// Length of each excerpt sampled by [`TranscriptionEngine::transcribe_preview`].
+const PREVIEW_EXCERPT_SECONDS: usize = 10;
+
+/// Number of excerpts sampled from long audio for a preview.
+const PREVIEW_EXCERPTS: usize = 12;
+
+/// Picks the sample ranges a preview transcribes: all of short audio, or
+/// [`PREVIEW_EXCERPTS`] evenly spaced excerpts covering both ends of long audio.
+fn preview_excerpts(len: usize, sample_rate: u32) -> Vec<std::ops::Range<usize>> {
+    let excerpt = PREVIEW_EXCERPT_SECONDS * sample_rate as usize;
+    if len <= excerpt * PREVIEW_EXCERPTS {
+        return std::iter::once(0..len).collect();
+    }
+
+    (0..PREVIEW_EXCERPTS)
+        .map(|i| i * (len - excerpt) / (PREVIEW_EXCERPTS - 1))
+        .map(|start| start..start + excerpt)
+        .collect()
+}
+
+/// Shortest stretch of audio worth decoding on its own processor.
+const MIN_PARALLEL_PART_SECONDS: usize = 30;
+
+/// Splits `len` samples into up to `n_processors` equal consecutive parts,
+/// each at least [`MIN_PARALLEL_PART_SECONDS`] long.
+fn parallel_parts(len: usize, n_processors: u32) -> Vec<std::ops::Range<usize>> {
+    let max_parts = len / (MIN_PARALLEL_PART_SECONDS * WHISPER_SAMPLE_RATE as usize);
+    let n = (n_processors as usize).min(max_parts).max(1);
+    (0..n).map(|i| i * len / n..(i + 1) * len / n).collect()
+}
+
+/// Aligns the words of two transcriptions, e.g. from different models or
+/// configs, regardless of how each split its segments.
+pub fn diff_segments(reference: &[Segment], hypothesis: &[Segment]) -> Vec<crate::text::WordEdit> {
+    crate::text::diff_words(&segments_text(reference), &segments_text(hypothesis))
+}
+
+/// Makes segment timestamps monotonic and non-overlapping.
+///
+/// A segment starting before its predecessor is moved to start with it, and a
+/// segment running past the next one's start is cut off there. Every segment
+/// keeps `end_ms >= start_ms`.
+pub fn fix_overlaps(segments: &mut [Segment]) {
+    for i in 0..segments.len() {
+        if i > 0 {
+            let previous_start = segments[i - 1].start_ms;
+            segments[i].start_ms = segments[i].start_ms.max(previous_start);
+
+            let start = segments[i].start_ms;
+            let previous = &mut segments[i - 1];
+            previous.end_ms = previous.end_ms.min(start);
+        }
+        let segment = &mut segments[i];
+        segment.end_ms = segment.end_ms.max(segment.start_ms);
+    }
+}
+
+/// Merges every segment shorter than `min_duration_ms` into the one before
+/// it, or the first into the one after, joining their text with a space.
+///
+/// Segments of different speakers are never merged. Does nothing when
+/// `min_duration_ms` is 0 or less.
+pub fn merge_short_segments(segments: &mut Vec<Segment>, min_duration_ms: i64) {
+    if min_duration_ms <= 0 {
+        return;
+    }
+
+    let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+    for segment in segments.drain(..) {
+        match merged.last_mut() {
+            Some(last)
+                if last.speaker_id == segment.speaker_id
+                    && (segment.duration_ms() < min_duration_ms || last.duration_ms() < min_duration_ms) =>
+            {
+                last.absorb(segment)
+            }
+            _ => merged.push(segment),
+        }
+    }
+    *segments = merged;
+}
+
+/// Loads the model at `path` into `slot`, recording the load time.
+fn load_model(path: &str, config: &WhisperConfig, slot: &mut ModelSlot) -> Result<()> {
+    tracing::info!("Loading Whisper model from: {}", path);
+
+    // Load the model
+    let load_start = Instant::now();
+    let ctx = open_context(path, config)?;
+    let load_time = load_start.elapsed();
+
+    slot.ctx = Some(Arc::new(ctx));
+    slot.load_time = Some(load_time);
+    slot.last_used = Instant::now();
+
+    tracing::info!("Whisper model loaded successfully in {}ms", load_time.as_millis());
+    Ok(())
+}
+
+/// Loads a whisper context from the model at `path` on the backend `config` selects.
+fn open_context(path: &str, config: &WhisperConfig) -> Result<WhisperContext> {
+    WhisperContext::new_with_params(path, context_params(config))
+        .map_err(|e| WhisperError::ContextInitError(format!("Failed to load model: {}", e)))
+}
+
+/// Builds the context parameters for `config`'s GPU and flash attention settings.
+fn context_params(config: &WhisperConfig) -> WhisperContextParameters<'static> {
+    let mut params = WhisperContextParameters::default();
+    params.use_gpu(config.use_gpu).flash_attn(config.flash_attention);
+    params
+}
+
+/// Returns whether contexts for `a` and `b` would run on the same backend,
+/// so one can be shared by both.
+fn same_backend(a: &WhisperConfig, b: &WhisperConfig) -> bool {
+    a.use_gpu == b.use_gpu && a.flash_attention == b.flash_attention
+}
+
+/// Unloads the model in `model` once it has been idle past its timeout.
+///
+/// Exits when the engine is dropped or idle unloading is disabled.
+fn watch_idle(model: Weak<Mutex<ModelSlot>>) {
+    loop {
+        let Some(model) = model.upgrade() else { return };
+        let Some(interval) = model.lock().unwrap().idle_tick(Instant::now()) else { return };
+        drop(model);
+        std::thread::sleep(interval);
+    }
+}
+
+/// Text of the segment covering a chunk that could not be transcribed.
+pub const UNINTELLIGIBLE_MARKER: &str = "[unintelligible]";
+
+/// Characters of the previous chunk's text carried into the next chunk's prompt.
+const CARRIED_CONTEXT_CHARS: usize = 200;
+
+/// Minimum sampling temperature for chunk retries.
+const FALLBACK_TEMPERATURE: f32 = 0.4;
+
+/// Returns the safer config used to retry a failed chunk: CPU only, greedy
+/// decoding and a raised temperature to escape degenerate decodes.
+pub fn fallback_config(config: &WhisperConfig) -> WhisperConfig {
+    WhisperConfig {
+        use_gpu: false,
+        flash_attention: false,
+        beam_size: 1,
+        temperature: config.temperature.max(FALLBACK_TEMPERATURE),
+        ..config.clone()
+    }
+}
+
+/// The decoder settings that control its text context.
+trait TextContextParams {
+    fn set_n_max_text_ctx(&mut self, n_max_text_ctx: c_int);
+    fn set_initial_prompt(&mut self, prompt: &str);
+}
+
+impl TextContextParams for FullParams<'_, '_> {
+    fn set_n_max_text_ctx(&mut self, n_max_text_ctx: c_int) {
+        FullParams::set_n_max_text_ctx(self, n_max_text_ctx);
+    }
+
+    fn set_initial_prompt(&mut self, prompt: &str) {
+        FullParams::set_initial_prompt(self, prompt);
+    }
+}
+
+/// Gives the decoder `prompt` as context, or no text context at all when
+/// `condition_on_previous_text` is off.
+///
+/// whisper.cpp refills the context from each decoded window whatever
+/// `no_context` says, so only an empty context budget stops conditioning.
+/// The prompt lives in that same budget, so it is dropped too.
+fn apply_text_context(params: &mut impl TextContextParams, condition_on_previous_text: bool, prompt: Option<&str>) {
+    if !condition_on_previous_text {
+        params.set_n_max_text_ctx(0);
+    } else if let Some(prompt) = prompt.filter(|p| !p.is_empty()) {
+        params.set_initial_prompt(prompt);
+    }
+}
+
+/// Returns the end of `text`, at most `max_chars` characters long, starting
+/// at a word boundary when the cut would split a word.
+fn context_tail(text: &str, max_chars: usize) -> &str {
+    let text = text.trim();
+    let tail = crate::text::tail_chars(text, max_chars);
+    let start = text.len() - tail.len();
+    if start > 0 && !text[..start].ends_with(char::is_whitespace) {
+        if let Some(space) = tail.find(char::is_whitespace) {
+            return tail[space..].trim_start();
+        }
+    }
+    tail
+}
+
+/// Rejects settings an English-only model cannot honour.
+fn check_english_only(language: &str, translate: bool) -> Result<()> {
+    if translate {
+        return Err(WhisperError::ConfigError(
+            "Translation requires a multilingual model; the loaded model is English-only".to_string(),
+        ));
+    }
+    if language != "auto" && language != "en" {
+        return Err(WhisperError::ConfigError(format!(
+            "Language '{}' requires a multilingual model; the loaded model is English-only",
+            language
+        )));
+    }
+    Ok(())
+}
+
+/// Appends one chunk's outcome, starting at `start_ms`, to `result`.
+fn stitch_chunk(
+    result: &mut TranscriptionResult,
+    outcome: Result<TranscriptionResult>,
+    start_ms: i64,
+    end_ms: i64,
+) {
+    match outcome {
+        Ok(chunk) => result.append(chunk, start_ms),
+        Err(e) => {
+            tracing::error!("Chunk {}-{}ms could not be transcribed: {}", start_ms, end_ms, e);
+            let mut marker = Segment::new(start_ms, end_ms, UNINTELLIGIBLE_MARKER.to_string());
+            marker.confidence = 0.0;
+            result.segments.push(marker);
+            result.average_confidence = result.average_confidence();
+            result.failed_ranges.push(FailedRange {
+                start_ms,
+                end_ms,
+                error: e.to_string(),
+            });
+        }
+    }
+}
+
+/// Wall-clock limit on decoding, checked by whisper.cpp between steps.
+struct Deadline {
+    at: Option<Instant>,
+    /// Set once the limit has stopped a decode.
+    timed_out: AtomicBool,
+}
+
+impl Deadline {
+    fn new(at: Option<Instant>) -> Self {
+        Self {
+            at,
+            timed_out: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns whether the limit has passed, remembering that it did.
+    fn expired(&self) -> bool {
+        let expired = self.at.is_some_and(|at| Instant::now() >= at);
+        if expired {
+            self.timed_out.store(true, Ordering::SeqCst);
+        }
+        expired
+    }
+}
+
+/// Abort callback that stops decoding once a [`Deadline`] has passed.
+unsafe extern "C" fn deadline_abort_callback(user_data: *mut c_void) -> bool {
+    !user_data.is_null() && (*(user_data as *const Deadline)).expired()
+}
+
+/// Token ids to mask out of every decoding step.
+struct SuppressedTokens {
+    ids: Vec<i32>,
+    n_vocab: i32,
+}
+
+/// Logits filter that makes the ids in a [`SuppressedTokens`] impossible to sample.
+unsafe extern "C" fn suppress_tokens_callback(
+    _ctx: *mut WhisperSysContext,
+    _state: *mut WhisperSysState,
+    _tokens: *const WhisperTokenData,
+    _n_tokens: c_int,
+    logits: *mut f32,
+    user_data: *mut c_void,
+) {
+    if logits.is_null() || user_data.is_null() {
+        return;
+    }
+    let suppressed = &*(user_data as *const SuppressedTokens);
+    let logits = std::slice::from_raw_parts_mut(logits, suppressed.n_vocab.max(0) as usize);
+    mask_logits(logits, &suppressed.ids);
+}
+
+/// Sets the logits of `ids` to negative infinity, ignoring out-of-range ids.
+fn mask_logits(logits: &mut [f32], ids: &[i32]) {
+    for &id in ids {
+        if let Some(logit) = usize::try_from(id).ok().and_then(|i| logits.get_mut(i)) {
+            *logit = f32::NEG_INFINITY;
+        }
+    }
+}
+
+/// Returns the id of the most probable language in `probs`, considering only
+/// `allowed` codes when any of them are known to Whisper.
+fn pick_language(probs: &[f32], allowed: &[String]) -> Option<i32> {
+    let allowed_ids: Vec<usize> = allowed
+        .iter()
+        .filter_map(|code| whisper_rs::get_lang_id(code))
+        .filter_map(|id| usize::try_from(id).ok())
+        .filter(|&id| id < probs.len())
+        .collect();
+
+    let candidates: Box<dyn Iterator<Item = usize>> = if allowed_ids.is_empty() {
+        Box::new(0..probs.len())
+    } else {
+        Box::new(allowed_ids.into_iter())
+    };
+
+    candidates
+        .max_by(|&a, &b| probs[a].total_cmp(&probs[b]))
+        .map(|id| id as i32)
+}
+
+/// Picks the decoding strategy from the config.
+///
+/// A `beam_size` above 1 selects beam search, and `best_of` is ignored.
+/// Otherwise decoding is greedy, sampling `best_of` candidates only when a
+/// nonzero temperature, initial or after an increment, makes them differ.
+fn build_sampling_strategy(config: &TranscriptionOptions) -> SamplingStrategy {
+    if config.beam_size > 1 {
+        return SamplingStrategy::BeamSearch {
+            beam_size: config.beam_size as i32,
+            patience: config.patience,
+        };
+    }
+    greedy_strategy(config)
+}
+
+/// Picks greedy decoding for `config`, ignoring its beam size.
+fn greedy_strategy(config: &TranscriptionOptions) -> SamplingStrategy {
+    let samples = config.temperature > 0.0 || config.temperature_increment > 0.0;
+    SamplingStrategy::Greedy {
+        best_of: if samples { config.best_of.max(1) as i32 } else { 1 },
+    }
+}
+
+/// Collects every token of a segment, including special tokens.
+fn segment_tokens(state: &WhisperState, segment: i32) -> Result<Vec<TokenInfo>> {
+    let num_tokens = state.full_n_tokens(segment)
+        .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token count: {}", e)))?;
+
+    (0..num_tokens)
+        .map(|j| {
+            let data = state.full_get_token_data(segment, j)
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token data: {}", e)))?;
+            let text = state.full_get_token_text_lossy(segment, j)
+                .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token text: {}", e)))?;
+            Ok(TokenInfo {
+                id: data.id,
+                text,
+                probability: data.p,
+            })
+        })
+        .collect()
+}
+
+/// Reads a segment's words from its token-level timestamps.
+fn segment_words(state: &WhisperState, ctx: &WhisperContext, segment: i32) -> Result<Vec<Word>> {
+    let num_tokens = state.full_n_tokens(segment)
+        .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token count: {}", e)))?;
+
+    let mut tokens = Vec::new();
+    for j in 0..num_tokens {
+        let data = state.full_get_token_data(segment, j)
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token data: {}", e)))?;
+        if data.id >= ctx.token_eot() {
+            continue;
+        }
+        let text = state.full_get_token_text_lossy(segment, j)
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token text: {}", e)))?;
+        // Token timestamps are in centiseconds, like segment timestamps
+        tokens.push((text, data.t0 * 10, data.t1 * 10, data.p));
+    }
+    Ok(group_words(tokens))
+}
+
+/// Joins `(text, start_ms, end_ms, probability)` text tokens into words; a
+/// token starting with whitespace begins a new word.
+fn group_words(tokens: Vec<(String, i64, i64, f32)>) -> Vec<Word> {
+    let mut words: Vec<(Word, usize)> = Vec::new();
+    for (text, start_ms, end_ms, probability) in tokens {
+        match words.last_mut() {
+            Some((word, count)) if !text.starts_with(char::is_whitespace) => {
+                word.text.push_str(&text);
+                word.end_ms = word.end_ms.max(end_ms);
+                word.probability += probability;
+                *count += 1;
+            }
+            _ => words.push((Word { start_ms, end_ms, text, probability }, 1)),
+        }
     }
+
+    words
+        .into_iter()
+        .filter(|(word, _)| !word.text.trim().is_empty())
+        .map(|(mut word, count)| {
+            word.text = word.text.trim().to_string();
+            word.probability /= count as f32;
+            word
+        })
+        .collect()
 }
 
-/// Resamples audio from source sample rate to 16kHz.
-fn resample_to_16khz(samples: &[f32], source_rate: u32) -> Vec<f32> {
-    if source_rate == 16000 {
-        return samples.to_vec();
-    }
-
-    let ratio = source_rate as f64 / 16000.0;
-    let new_len = (samples.len() as f64 / ratio) as usize;
-    let mut resampled = Vec::with_capacity(new_len);
-
-    for i in 0..new_len {
-        let src_idx = (i as f64 * ratio) as usize;
-        if src_idx < samples.len() {
-            // Linear interpolation
-            let frac = (i as f64 * ratio) - src_idx as f64;
-            let sample = if src_idx + 1 < samples.len() {
-                samples[src_idx] * (1.0 - frac as f32) + samples[src_idx + 1] * frac as f32
-            } else {
-                samples[src_idx]
-            };
-            resampled.push(sample);
+/// Computes a segment's confidence and average log probability as the means
+/// over its text tokens.
+///
+/// Special tokens (timestamps, end-of-text, ...) are excluded.
+fn segment_scores(state: &WhisperState, ctx: &WhisperContext, segment: i32) -> Result<(f32, f32)> {
+    let num_tokens = state.full_n_tokens(segment)
+        .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token count: {}", e)))?;
+
+    let mut total = 0.0;
+    let mut total_logprob = 0.0;
+    let mut count = 0;
+
+    for j in 0..num_tokens {
+        let token = state.full_get_token_data(segment, j)
+            .map_err(|e| WhisperError::TranscriptionError(format!("Failed to get token data: {}", e)))?;
+
+        if token.id >= ctx.token_eot() {
+            continue;
         }
+
+        total += token.p;
+        total_logprob += token.plog;
+        count += 1;
+    }
+
+    if count == 0 {
+        return Ok((0.0, 0.0));
+    }
+
+    Ok((total / count as f32, total_logprob / count as f32))
+}
+
+/// Merges per-channel results into one, tagging segments with their channel index.
+fn merge_channel_results(results: Vec<TranscriptionResult>) -> TranscriptionResult {
+    let mut merged = TranscriptionResult::empty();
+
+    for (channel, result) in results.into_iter().enumerate() {
+        if merged.language.is_empty() && !result.text.is_empty() {
+            merged.language = result.language;
+        }
+        merged.processing_time_ms += result.processing_time_ms;
+        merged.resample_time_ms += result.resample_time_ms;
+        merged.inference_time_ms += result.inference_time_ms;
+        merged.audio_duration_ms = merged.audio_duration_ms.max(result.audio_duration_ms);
+        merged.processed_duration_ms = merged.processed_duration_ms.max(result.processed_duration_ms);
+        merged.had_speech |= result.had_speech;
+
+        for mut segment in result.segments {
+            segment.speaker_id = Some(channel as u32);
+            merged.segments.push(segment);
+        }
+    }
+
+    // Stable sort keeps channel order for segments starting at the same time
+    merged.segments.sort_by_key(|s| s.start_ms);
+
+    merged.text = segments_text(&merged.segments);
+    merged.average_confidence = merged.average_confidence();
+
+    merged
+}
+
+/// RMS level `auto_gain` raises quiet audio to (-20 dBFS).
+const AUTO_GAIN_TARGET_RMS: f32 = 0.1;
+
+/// Largest gain `auto_gain` applies (+20dB).
+const AUTO_GAIN_MAX: f32 = 10.0;
+
+/// Level above which `compress_dynamics` reduces peaks, in dBFS.
+const COMPRESSOR_THRESHOLD_DB: f32 = -20.0;
+
+/// Compression ratio used by `compress_dynamics`.
+const COMPRESSOR_RATIO: f32 = 4.0;
+
+/// Brings quiet audio up to [`AUTO_GAIN_TARGET_RMS`], never attenuating.
+fn apply_auto_gain(audio: &mut AudioBuffer) {
+    if audio.rms() >= AUTO_GAIN_TARGET_RMS {
+        return;
+    }
+
+    let gain = audio.normalize_rms(AUTO_GAIN_TARGET_RMS, AUTO_GAIN_MAX);
+    tracing::debug!("Auto gain applied {:.1}x", gain);
+}
+
+/// Resamples audio from source sample rate to 16kHz.
+fn resample_to_16khz(samples: &[f32], source_rate: u32, quality: ResampleQuality) -> Result<Vec<f32>> {
+    if source_rate == WHISPER_SAMPLE_RATE {
+        return Ok(samples.to_vec());
     }
 
-    resampled
+    let buffer = AudioBuffer::from_samples(samples.to_vec(), source_rate)
+        .resample_with(WHISPER_SAMPLE_RATE, quality)?;
+    Ok(buffer.samples().to_vec())
 }
 
 #[cfg(test)]
@@ -335,19 +2363,796 @@ mod tests {
     fn test_result_realtime_factor() {
         let result = TranscriptionResult {
             text: "test".to_string(),
-            segments: vec![],
             language: "en".to_string(),
             processing_time_ms: 500,
             audio_duration_ms: 1000,
+            processed_duration_ms: 1000,
+            ..TranscriptionResult::empty()
         };
         assert_eq!(result.realtime_factor(), 0.5);
     }
 
+    fn sample_result() -> TranscriptionResult {
+        TranscriptionResult {
+            text: "One two three".to_string(),
+            segments: vec![
+                Segment::new(0, 1000, " One".to_string()),
+                Segment::new(1000, 2000, " two".to_string()),
+                Segment::new(3000, 4500, " three".to_string()),
+            ],
+            language: "en".to_string(),
+            audio_duration_ms: 5000,
+            processed_duration_ms: 5000,
+            ..TranscriptionResult::empty()
+        }
+    }
+
+    #[test]
+    fn test_segment_at_boundaries() {
+        let result = sample_result();
+        assert_eq!(result.segment_at(0).unwrap().text, " One");
+        assert_eq!(result.segment_at(999).unwrap().text, " One");
+        assert_eq!(result.segment_at(1000).unwrap().text, " two");
+        assert!(result.segment_at(2000).is_none());
+        assert!(result.segment_at(2500).is_none());
+        assert_eq!(result.segment_at(3000).unwrap().text, " three");
+        assert!(result.segment_at(4500).is_none());
+        assert!(result.segment_at(-1).is_none());
+    }
+
+    #[test]
+    fn test_segments_in_range() {
+        let result = sample_result();
+        let texts = |start, end| -> Vec<&str> {
+            result.segments_in_range(start, end).map(|s| s.text.as_str()).collect()
+        };
+        assert_eq!(texts(0, 1000), vec![" One"]);
+        assert_eq!(texts(999, 1001), vec![" One", " two"]);
+        assert_eq!(texts(2000, 3000), Vec::<&str>::new());
+        assert_eq!(texts(0, 10_000), vec![" One", " two", " three"]);
+    }
+
+    #[test]
+    fn test_average_confidence() {
+        let mut result = TranscriptionResult::empty();
+        assert_eq!(result.average_confidence(), 0.0);
+
+        let mut long = Segment::new(0, 3000, " Long".to_string());
+        long.confidence = 0.9;
+        let mut short = Segment::new(3000, 4000, " short".to_string());
+        short.confidence = 0.5;
+        result.segments = vec![long, short];
+        assert!((result.average_confidence() - 0.8).abs() < 1e-6);
+
+        // Kept up to date when results are combined
+        let mut combined = TranscriptionResult::empty();
+        combined.append(result, 0);
+        assert!((combined.average_confidence - 0.8).abs() < 1e-6);
+
+        let instant = vec![Segment::new(500, 500, " a".to_string()), Segment::new(500, 500, " b".to_string())];
+        assert_eq!(average_confidence(&instant), 1.0);
+    }
+
+    #[test]
+    fn test_total_speech_ms() {
+        assert_eq!(sample_result().total_speech_ms(), 3500);
+        assert_eq!(TranscriptionResult::empty().total_speech_ms(), 0);
+    }
+
+    #[test]
+    fn test_word_count_and_rate() {
+        let mut result = sample_result();
+        result.segments.push(Segment::new(4500, 4500, " - and more!".to_string()));
+        assert_eq!(result.word_count(), 5);
+        // 5 words over 3.5s of speech
+        assert!((result.words_per_minute() - 5.0 * 60.0 / 3.5).abs() < 1e-9);
+
+        let empty = TranscriptionResult::empty();
+        assert_eq!(empty.word_count(), 0);
+        assert_eq!(empty.words_per_minute(), 0.0);
+    }
+
+    #[test]
+    fn test_by_speaker() {
+        let mut result = sample_result();
+        result.segments[0].speaker_id = Some(1);
+        result.segments[2].speaker_id = Some(1);
+        result.segments.push(Segment::new(5000, 6000, " four".to_string()));
+        result.segments[3].speaker_id = Some(0);
+
+        let groups = result.by_speaker();
+        assert_eq!(groups.keys().copied().collect::<Vec<_>>(), vec![0, 1, UNKNOWN_SPEAKER]);
+        assert_eq!(groups[&1].len(), 2);
+        assert_eq!(result.speaker_transcript(1), "One three");
+        assert_eq!(result.speaker_transcript(UNKNOWN_SPEAKER), "two");
+        assert_eq!(result.speaker_transcript(7), "");
+    }
+
+    #[test]
+    fn test_append() {
+        let mut result = sample_result();
+        result.processing_time_ms = 1000;
+        result.processed_duration_ms = 2500;
+        let mut other = sample_result();
+        other.language = "fr".to_string();
+        other.processing_time_ms = 1500;
+        other.processed_duration_ms = 2500;
+        other.had_speech = true;
+        other.raw_text = Some("[_BEG_] One[_TT_50]".to_string());
+        other.cancelled = true;
+        result.append(other, 5000);
+
+        assert_eq!(result.text, "One two three One two three");
+        assert_eq!(result.language, "en");
+        assert_eq!(result.segments.len(), 6);
+        assert_eq!((result.segments[3].start_ms, result.segments[5].end_ms), (5000, 9500));
+        assert_eq!(result.audio_duration_ms, 10_000);
+        assert_eq!(result.realtime_factor(), 0.25);
+        assert_eq!(result.processed_realtime_factor(), 0.5);
+        assert!(result.had_speech);
+        assert_eq!(result.raw_text.as_deref(), Some("[_BEG_] One[_TT_50]"));
+        assert!(result.cancelled);
+
+        let mut empty = TranscriptionResult::empty();
+        empty.append(sample_result(), 0);
+        assert_eq!(empty.text, "One two three");
+        assert_eq!(empty.language, "en");
+    }
+
+    #[test]
+    fn test_last_result_cleared() {
+        let engine = TranscriptionEngine::with_defaults();
+        assert_eq!(engine.last_segment_count(), None);
+
+        // Nothing is kept unless asked for
+        engine.remember(Ok(sample_result())).unwrap();
+        assert_eq!(engine.last_segment_count(), None);
+
+        engine.set_keep_last_result(true);
+        engine.remember(Ok(sample_result())).unwrap();
+        assert_eq!(engine.last_segment_count(), Some(3));
+        assert_eq!(engine.last_segment(2).unwrap().text, " three");
+        assert!(engine.last_segment(3).is_none());
+
+        engine.clear_last_result();
+        assert!(engine.last_result().is_none());
+        assert!(engine.last_segment(0).is_none());
+
+        engine.remember(Ok(sample_result())).unwrap();
+        engine.set_keep_last_result(false);
+        assert!(engine.last_result().is_none());
+    }
+
+    #[test]
+    fn test_transcript_text_spacing() {
+        let segments = vec![
+            Segment::new(0, 1000, " 今日は".to_string()),
+            Segment::new(1000, 2000, " 晴れ。".to_string()),
+        ];
+        let engine = TranscriptionEngine::with_defaults();
+        assert_eq!(engine.transcript_text(&segments, "ja"), "今日は晴れ。");
+        assert_eq!(engine.transcript_text(&segments, "en"), "今日は 晴れ。");
+
+        let config = WhisperConfig { join_with_spaces: Some(true), ..WhisperConfig::default() };
+        let engine = TranscriptionEngine::new(config);
+        assert_eq!(engine.transcript_text(&segments, "ja"), "今日は 晴れ。");
+    }
+
+    #[test]
+    fn test_language_context() {
+        let mut config = WhisperConfig::default();
+        config
+            .language_model_paths
+            .insert("fr".to_string(), "/nonexistent/fr.bin".to_string());
+        let engine = TranscriptionEngine::new(config);
+
+        assert!(engine.detects_language_up_front("auto"));
+        assert!(!engine.detects_language_up_front("de"));
+        assert!(matches!(engine.language_context("en"), Ok(None)));
+        assert!(matches!(engine.language_context("fr"), Err(WhisperError::ModelNotFound(_))));
+    }
+
+    #[test]
+    fn test_context_window_seconds() {
+        assert_eq!(context_window_seconds(1500), 30);
+        assert_eq!(context_window_seconds(3000), 60);
+        assert_eq!(context_window_seconds(0), 0);
+        assert!(TranscriptionEngine::with_defaults().max_audio_context().is_err());
+    }
+
+    #[test]
+    fn test_preview_excerpts() {
+        let minute = 60 * 16000;
+        assert_eq!(preview_excerpts(minute, 16000), vec![0..minute]);
+
+        let excerpts = preview_excerpts(60 * minute, 16000);
+        assert_eq!(excerpts.len(), PREVIEW_EXCERPTS);
+        assert_eq!(excerpts[0], 0..160_000);
+        assert_eq!(excerpts[PREVIEW_EXCERPTS - 1].end, 60 * minute);
+        assert!(excerpts.windows(2).all(|pair| pair[0].end <= pair[1].start));
+    }
+
+    #[test]
+    fn test_parallel_parts() {
+        let minute = 60 * 16000;
+        assert_eq!(parallel_parts(minute, 1), vec![0..minute]);
+        assert_eq!(parallel_parts(minute, 4), vec![0..minute / 2, minute / 2..minute]);
+        assert_eq!(parallel_parts(3 * minute, 3), vec![0..minute, minute..2 * minute, 2 * minute..3 * minute]);
+        assert_eq!(parallel_parts(16000, 8), vec![0..16000]);
+        assert_eq!(parallel_parts(minute, 0), vec![0..minute]);
+    }
+
+    #[test]
+    fn test_resegment_fixed() {
+        let mut result = TranscriptionResult::empty();
+        result.segments = vec![
+            Segment::new(0, 3000, " aaaa bbbb cccc".to_string()),
+            Segment::new(3000, 4000, " dd".to_string()),
+        ];
+        result.segments[1].confidence = 0.5;
+
+        let windows = result.resegment_fixed(2000);
+        let cells: Vec<_> = windows.iter().map(|s| (s.start_ms, s.end_ms, s.text.as_str())).collect();
+        assert_eq!(cells, vec![(0, 2000, "aaaa bbbb"), (2000, 4000, "cccc dd")]);
+        assert_eq!(windows[1].confidence, 0.5);
+
+        // Originals are untouched
+        assert_eq!(result.segments.len(), 2);
+        assert_eq!(result.resegment_fixed(0).len(), 2);
+    }
+
+    #[test]
+    fn test_resegment_fixed_word_timestamps() {
+        // Evenly spread characters would put "later" in the first window
+        let mut segment = Segment::new(0, 4000, " a b c later".to_string());
+        for (text, start, end) in [("a", 0, 200), ("b", 200, 400), ("c", 400, 600), ("later", 2900, 3500)] {
+            segment.words.push(Word { start_ms: start, end_ms: end, text: text.to_string(), probability: 0.9 });
+        }
+        let mut result = TranscriptionResult::empty();
+        result.segments = vec![segment];
+
+        let windows = result.resegment_fixed(2000);
+        let cells: Vec<_> = windows.iter().map(|s| (s.start_ms, s.text.as_str())).collect();
+        assert_eq!(cells, vec![(0, "a b c"), (2000, "later")]);
+        assert_eq!(windows[0].words.len(), 3);
+        assert_eq!(windows[1].words[0].start_ms, 2900);
+    }
+
+    #[test]
+    fn test_fix_overlaps() {
+        let mut segments = vec![
+            Segment::new(0, 1500, " a".to_string()),
+            Segment::new(1000, 2000, " b".to_string()),
+            Segment::new(800, 900, " c".to_string()),
+            Segment::new(2500, 2400, " d".to_string()),
+        ];
+        fix_overlaps(&mut segments);
+
+        let times: Vec<_> = segments.iter().map(|s| (s.start_ms, s.end_ms)).collect();
+        assert_eq!(times, vec![(0, 1000), (1000, 1000), (1000, 1000), (2500, 2500)]);
+
+        let mut clean = vec![Segment::new(0, 1000, " a".to_string()), Segment::new(1200, 2000, " b".to_string())];
+        fix_overlaps(&mut clean);
+        assert_eq!((clean[0].end_ms, clean[1].start_ms), (1000, 1200));
+    }
+
+    #[test]
+    fn test_merge_short_segments() {
+        let mut segments = vec![
+            Segment::new(0, 100, " So".to_string()),
+            Segment::new(100, 1500, " the plan".to_string()),
+            Segment::new(1500, 1650, " is".to_string()),
+            Segment::new(2000, 3000, " ready.".to_string()),
+        ];
+        segments[1].confidence = 0.5;
+        merge_short_segments(&mut segments, 200);
+
+        let merged: Vec<_> = segments.iter().map(|s| (s.start_ms, s.end_ms, s.text.as_str())).collect();
+        assert_eq!(merged, vec![(0, 1650, " So the plan is"), (2000, 3000, " ready.")]);
+        assert!(segments[0].confidence < 0.6);
+
+        // Other speakers' segments stay separate
+        let mut speakers = vec![Segment::new(0, 100, " Hi".to_string()), Segment::new(100, 900, " Hello".to_string())];
+        speakers[1].speaker_id = Some(1);
+        merge_short_segments(&mut speakers, 200);
+        assert_eq!(speakers.len(), 2);
+
+        merge_short_segments(&mut segments, 0);
+        assert_eq!(segments.len(), 2);
+    }
+
+    /// Records the text context settings applied to it.
+    #[derive(Default)]
+    struct RecordedParams {
+        n_max_text_ctx: Option<c_int>,
+        prompt: Option<String>,
+    }
+
+    impl TextContextParams for RecordedParams {
+        fn set_n_max_text_ctx(&mut self, n_max_text_ctx: c_int) {
+            self.n_max_text_ctx = Some(n_max_text_ctx);
+        }
+
+        fn set_initial_prompt(&mut self, prompt: &str) {
+            self.prompt = Some(prompt.to_string());
+        }
+    }
+
+    #[test]
+    fn test_apply_text_context() {
+        let mut params = RecordedParams::default();
+        apply_text_context(&mut params, true, Some("Zeffut"));
+        assert_eq!(params.n_max_text_ctx, None);
+        assert_eq!(params.prompt.as_deref(), Some("Zeffut"));
+
+        // Conditioning off empties the context, prompt included
+        let mut params = RecordedParams::default();
+        apply_text_context(&mut params, false, Some("Zeffut"));
+        assert_eq!(params.n_max_text_ctx, Some(0));
+        assert_eq!(params.prompt, None);
+
+        let mut params = RecordedParams::default();
+        apply_text_context(&mut params, true, Some(""));
+        assert_eq!(params.prompt, None);
+    }
+
+    #[test]
+    fn test_check_english_only() {
+        assert!(check_english_only("en", false).is_ok());
+        assert!(check_english_only("auto", false).is_ok());
+        assert!(matches!(check_english_only("fr", false), Err(WhisperError::ConfigError(_))));
+        assert!(matches!(check_english_only("en", true), Err(WhisperError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let unlimited = Deadline::new(None);
+        assert!(!unlimited.expired());
+        assert!(!unlimited.timed_out.load(Ordering::SeqCst));
+
+        let future = Deadline::new(Some(Instant::now() + Duration::from_secs(60)));
+        assert!(!unsafe { deadline_abort_callback(&future as *const _ as *mut c_void) });
+
+        let past = Deadline::new(Some(Instant::now()));
+        assert!(unsafe { deadline_abort_callback(&past as *const _ as *mut c_void) });
+        assert!(past.timed_out.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_group_words() {
+        let token = |text: &str, start_ms, end_ms, p| (text.to_string(), start_ms, end_ms, p);
+        let words = group_words(vec![
+            token(" Hel", 0, 200, 0.8),
+            token("lo", 200, 400, 0.6),
+            token(",", 400, 420, 1.0),
+            token(" world", 500, 900, 0.9),
+            token(" ", 900, 910, 0.1),
+        ]);
+        assert_eq!(words.len(), 2);
+        assert_eq!((words[0].text.as_str(), words[0].start_ms, words[0].end_ms), ("Hello,", 0, 420));
+        assert!((words[0].probability - 0.8).abs() < 1e-6);
+        assert_eq!((words[1].text.as_str(), words[1].start_ms, words[1].end_ms), ("world", 500, 900));
+
+        let mut segment = Segment::new(0, 900, " Hello, world".to_string());
+        segment.words = words;
+        segment.shift(1000);
+        assert_eq!((segment.start_ms, segment.words[1].end_ms), (1000, 1900));
+    }
+
+    #[test]
+    fn test_context_tail() {
+        assert_eq!(context_tail("  short text ", 200), "short text");
+        assert_eq!(context_tail("the quick brown fox", 9), "brown fox");
+        assert_eq!(context_tail("the quick brown fox", 7), "fox");
+        assert_eq!(context_tail("unbroken", 4), "oken");
+        assert_eq!(context_tail("café olé", 3), "olé");
+        assert_eq!(context_tail("", 10), "");
+    }
+
+    #[test]
+    fn test_transcribe_wav_chunked_reads_every_chunk() {
+        let path = std::env::temp_dir().join("whisper-core-wav-chunked-test.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..20000 {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        // Without a model every window fails, but each one is still accounted for
+        let engine = TranscriptionEngine::with_defaults();
+        let result = engine.transcribe_wav_chunked(&path, 1);
+        std::fs::remove_file(&path).unwrap();
+        let result = result.unwrap();
+        let ranges: Vec<_> = result.failed_ranges.iter().map(|r| (r.start_ms, r.end_ms)).collect();
+        assert_eq!(ranges, vec![(0, 1000), (1000, 2000), (2000, 2500)]);
+        assert_eq!(result.audio_duration_ms, 2500);
+    }
+
+    #[test]
+    fn test_transcribe_chunked_cancelled_before_start() {
+        let engine = TranscriptionEngine::with_defaults();
+        let audio = AudioBuffer::from_samples(vec![0.0; 40_000], 16000);
+
+        engine.cancel();
+        let result = engine.transcribe_chunked(&audio, 1).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.audio_duration_ms, 0);
+        assert!(result.segments.is_empty());
+
+        // The request ended with that call
+        let result = engine.transcribe_chunked(&audio, 1).unwrap();
+        assert!(!result.cancelled);
+        assert_eq!(result.audio_duration_ms, 2500);
+    }
+
+    #[test]
+    fn test_stitch_chunk() {
+        let mut result = TranscriptionResult::empty();
+        let first = TranscriptionResult {
+            text: "Hello".to_string(),
+            segments: vec![Segment::new(500, 1500, " Hello".to_string())],
+            language: "en".to_string(),
+            processing_time_ms: 100,
+            audio_duration_ms: 30_000,
+            processed_duration_ms: 30_000,
+            ..TranscriptionResult::empty()
+        };
+        stitch_chunk(&mut result, Ok(first.clone()), 0, 30_000);
+        stitch_chunk(
+            &mut result,
+            Err(WhisperError::TranscriptionError("boom".to_string())),
+            30_000,
+            60_000,
+        );
+        stitch_chunk(&mut result, Ok(first), 60_000, 70_000);
+
+        let times: Vec<_> = result.segments.iter().map(|s| (s.start_ms, s.end_ms)).collect();
+        assert_eq!(times, vec![(500, 1500), (30_000, 60_000), (60_500, 61_500)]);
+        assert_eq!(result.segments[1].text, UNINTELLIGIBLE_MARKER);
+        assert_eq!(result.failed_ranges.len(), 1);
+        assert_eq!((result.failed_ranges[0].start_ms, result.failed_ranges[0].end_ms), (30_000, 60_000));
+        assert!(result.failed_ranges[0].error.contains("boom"));
+        assert_eq!(result.processing_time_ms, 200);
+        assert_eq!(result.language, "en");
+    }
+
+    #[test]
+    fn test_fallback_config() {
+        let config = WhisperConfig::default().beam_size(5);
+        let fallback = fallback_config(&config);
+        assert!(!fallback.use_gpu);
+        assert_eq!(fallback.beam_size, 1);
+
+        // The retry's context really runs on the CPU
+        let params = context_params(&fallback);
+        assert!(!params.use_gpu && !params.flash_attn);
+        let params = context_params(&config);
+        assert!(params.use_gpu && params.flash_attn);
+
+        // A CPU-only engine can share its model with the fallback
+        assert!(!same_backend(&config, &fallback));
+        let cpu_only = WhisperConfig { use_gpu: false, flash_attention: false, ..config.clone() };
+        assert!(same_backend(&cpu_only, &fallback));
+        assert_eq!(fallback.temperature, FALLBACK_TEMPERATURE);
+        assert_eq!(fallback.language.source, config.language.source);
+    }
+
+    #[test]
+    fn test_pick_language() {
+        let en = whisper_rs::get_lang_id("en").unwrap() as usize;
+        let fr = whisper_rs::get_lang_id("fr").unwrap() as usize;
+        let de = whisper_rs::get_lang_id("de").unwrap() as usize;
+        let mut probs = vec![0.0; whisper_rs::get_lang_max_id() as usize + 1];
+        probs[en] = 0.5;
+        probs[fr] = 0.2;
+        probs[de] = 0.3;
+
+        assert_eq!(pick_language(&probs, &[]), Some(en as i32));
+        let allowed = vec!["fr".to_string(), "de".to_string()];
+        assert_eq!(pick_language(&probs, &allowed), Some(de as i32));
+        // Unknown codes fall back to unrestricted detection
+        assert_eq!(pick_language(&probs, &["xx".to_string()]), Some(en as i32));
+    }
+
+    #[test]
+    fn test_mask_logits() {
+        let mut logits = vec![0.5; 4];
+        mask_logits(&mut logits, &[1, 3, -1, 10]);
+        assert_eq!(logits, vec![0.5, f32::NEG_INFINITY, 0.5, f32::NEG_INFINITY]);
+    }
+
+    #[test]
+    fn test_build_sampling_strategy() {
+        let greedy = build_sampling_strategy(&TranscriptionOptions::default());
+        assert!(matches!(greedy, SamplingStrategy::Greedy { best_of: 1 }));
+
+        let config = WhisperConfig::default().beam_size(5).patience(2.0);
+        match build_sampling_strategy(&config.transcription_options()) {
+            SamplingStrategy::BeamSearch { beam_size, patience } => {
+                assert_eq!(beam_size, 5);
+                assert_eq!(patience, 2.0);
+            }
+            other => panic!("expected beam search, got {:?}", other),
+        }
+
+        // Beam search wins over best_of
+        let config = WhisperConfig::default().beam_size(3).best_of(5);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::BeamSearch { beam_size: 3, .. }));
+
+        // A beam of 1 is greedy
+        let config = WhisperConfig::default().beam_size(1);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 1 }));
+
+        // The greedy fallback keeps best_of but not the beam
+        let config = WhisperConfig::default().beam_size(5).best_of(3);
+        assert!(matches!(greedy_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 3 }));
+    }
+
+    #[test]
+    fn test_build_sampling_strategy_best_of() {
+        // Temperature fallback samples candidates
+        let config = WhisperConfig::default().best_of(5);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 5 }));
+
+        // So does a nonzero starting temperature without fallback
+        let config = WhisperConfig { temperature: 0.4, temperature_increment: 0.0, ..WhisperConfig::default() }.best_of(3);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 3 }));
+
+        // Pure argmax decoding has nothing to sample
+        let config = WhisperConfig { temperature: 0.0, temperature_increment: 0.0, ..WhisperConfig::default() }.best_of(5);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 1 }));
+
+        let config = WhisperConfig::default().best_of(0);
+        assert!(matches!(build_sampling_strategy(&config.transcription_options()), SamplingStrategy::Greedy { best_of: 1 }));
+    }
+
+    #[test]
+    fn test_segment_tokens_serialization() {
+        let mut segment = Segment::new(0, 1000, " Hi".to_string());
+        let json = serde_json::to_string(&segment).unwrap();
+        assert!(!json.contains("tokens"));
+
+        segment.tokens.push(TokenInfo {
+            id: 2421,
+            text: " Hi".to_string(),
+            probability: 0.9,
+        });
+        let parsed: Segment = serde_json::from_str(&serde_json::to_string(&segment).unwrap()).unwrap();
+        assert_eq!(parsed.tokens, segment.tokens);
+        let legacy: Segment = serde_json::from_str(&json).unwrap();
+        assert!(legacy.tokens.is_empty());
+    }
+
+    #[test]
+    fn test_to_timestamped_text() {
+        let mut result = sample_result();
+        result.segments[2].start_ms = 3_723_456;
+        assert_eq!(
+            result.to_timestamped_text(TimestampPrecision::Seconds),
+            "[00:00:00] One\n[00:00:01] two\n[01:02:03] three\n"
+        );
+        assert!(result
+            .to_timestamped_text(TimestampPrecision::Milliseconds)
+            .ends_with("[01:02:03.456] three\n"));
+        assert_eq!(TranscriptionResult::empty().to_timestamped_text(TimestampPrecision::Seconds), "");
+    }
+
+    #[test]
+    fn test_segment_by_words() {
+        let word = |text: &str, start_ms, end_ms| Word {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            probability: 0.5,
+        };
+        let mut result = TranscriptionResult::empty();
+        let mut first = Segment::new(0, 1500, " one two three".to_string());
+        first.words = vec![word("one", 0, 400), word("two", 500, 900), word("three", 1000, 1500)];
+        let mut second = Segment::new(1600, 2500, " four five".to_string());
+        second.words = vec![word("four", 1600, 2000), word("five", 2100, 2500)];
+        let mut other_speaker = Segment::new(2600, 3000, " six".to_string());
+        other_speaker.speaker_id = Some(1);
+        other_speaker.words = vec![word("six", 2600, 3000)];
+        result.segments = vec![first, second, Segment::new(2500, 2600, " no words".to_string()), other_speaker];
+
+        let regrouped = result.segment_by_words(2);
+        let summary: Vec<_> = regrouped
+            .iter()
+            .map(|s| (s.text.as_str(), s.start_ms, s.end_ms, s.speaker_id))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("one two", 0, 900, None),
+                ("three four", 1000, 2000, None),
+                ("five", 2100, 2500, None),
+                ("six", 2600, 3000, Some(1)),
+            ]
+        );
+        assert!((regrouped[0].confidence - 0.5).abs() < 1e-6);
+        // The original segments are untouched
+        assert_eq!(result.segments.len(), 4);
+        assert_eq!(result.segment_by_words(0).len(), 6);
+    }
+
+    #[test]
+    fn test_raw_segments_text() {
+        let mut result = TranscriptionResult::empty();
+        for raw in [" Hello,", " world."] {
+            let mut segment = Segment::new(0, 1000, raw.trim().to_string());
+            segment.raw_text = Some(raw.to_string());
+            result.segments.push(segment);
+        }
+        assert_eq!(result.raw_segments_text().as_deref(), Some(" Hello, world."));
+
+        result.segments.push(Segment::new(1000, 2000, "Again".to_string()));
+        assert_eq!(result.raw_segments_text(), None);
+    }
+
+    #[test]
+    fn test_to_srt_and_vtt() {
+        let mut result = sample_result();
+        result.segments[2].end_ms = 3_723_456;
+        result.segments.insert(1, Segment::new(900, 1000, "  ".to_string()));
+
+        assert_eq!(
+            result.to_srt(),
+            "1\n00:00:00,000 --> 00:00:01,000\nOne\n\n\
+             2\n00:00:01,000 --> 00:00:02,000\ntwo\n\n\
+             3\n00:00:03,000 --> 01:02:03,456\nthree\n\n"
+        );
+        let vtt = result.to_vtt();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nOne\n\n"));
+        assert!(vtt.ends_with("00:00:03.000 --> 01:02:03.456\nthree\n\n"));
+        assert_eq!(TranscriptionResult::empty().to_srt(), "");
+    }
+
+    #[test]
+    fn test_to_csv_escaping() {
+        let mut quoted = Segment::new(1000, 2500, " Well, she said \"hi\"".to_string());
+        quoted.speaker_id = Some(2);
+        quoted.avg_logprob = -0.25;
+        let result = TranscriptionResult {
+            segments: vec![
+                Segment::new(0, 1000, " Plain text".to_string()),
+                quoted,
+                Segment::new(2500, 3000, " two\nlines".to_string()),
+            ],
+            language: "en".to_string(),
+            audio_duration_ms: 3000,
+            processed_duration_ms: 3000,
+            ..TranscriptionResult::empty()
+        };
+
+        assert_eq!(
+            result.to_csv(),
+            "start_ms,end_ms,duration_ms,confidence,avg_logprob,speaker_id,text\r\n\
+             0,1000,1000,1.0000,0.0000,,Plain text\r\n\
+             1000,2500,1500,1.0000,-0.2500,2,\"Well, she said \"\"hi\"\"\"\r\n\
+             2500,3000,500,1.0000,0.0000,,\"two\nlines\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_model_load_time_unset_before_initialize() {
+        let mut engine = TranscriptionEngine::new(WhisperConfig::with_model_path("/nonexistent.bin"));
+        assert_eq!(engine.model_load_time_ms(), None);
+        assert!(engine.initialize().is_err());
+        assert_eq!(engine.model_load_time_ms(), None);
+    }
+
+    #[test]
+    fn test_idle_tick() {
+        let start = Instant::now();
+        let mut slot = ModelSlot::new();
+        slot.last_used = start;
+        slot.idle_timeout = Some(Duration::from_secs(60));
+        slot.watcher_running = true;
+
+        assert_eq!(slot.idle_tick(start + Duration::from_secs(30)), Some(Duration::from_secs(1)));
+        assert!(!slot.is_idle(start + Duration::from_secs(59)));
+        assert!(slot.is_idle(start + Duration::from_secs(60)));
+
+        // Disabling stops the watcher at its next check
+        slot.idle_timeout = None;
+        assert_eq!(slot.idle_tick(start), None);
+        assert!(!slot.watcher_running);
+        assert!(!slot.is_idle(start + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_model_use_touches_on_drop() {
+        let engine = TranscriptionEngine::with_defaults();
+        engine.set_idle_unload(Some(Duration::from_secs(60)));
+        assert_eq!(engine.idle_unload(), Some(Duration::from_secs(60)));
+
+        // A decode that outlasted the timeout leaves the model freshly used
+        let decode_start = Instant::now();
+        engine.model.lock().unwrap().last_used = decode_start - Duration::from_secs(120);
+        drop(ModelUse(&engine.model));
+        let slot = engine.model.lock().unwrap();
+        assert!(slot.last_used >= decode_start);
+        assert!(!slot.is_idle(decode_start));
+    }
+
+    #[test]
+    fn test_plan() {
+        let engine = TranscriptionEngine::with_defaults();
+        // 65 seconds at 48kHz
+        let audio = AudioBuffer::from_samples(vec![0.0; 65 * 48_000], 48_000);
+        let plan = engine.plan(&audio);
+        assert_eq!(plan.chunk_count(), 3);
+        assert_eq!(plan.chunks, vec![(0, 30_000), (30_000, 60_000), (60_000, 65_000)]);
+        assert_eq!(plan.audio_duration_ms, 65_000);
+        assert_eq!(plan.estimated_time_ms, engine.estimate_time(&audio).as_millis() as u64);
+
+        assert!(engine.plan(&AudioBuffer::from_samples(Vec::new(), 16000)).chunks.is_empty());
+    }
+
+    #[test]
+    fn test_estimate_time() {
+        let engine = TranscriptionEngine::with_defaults();
+        let audio = AudioBuffer::from_samples(vec![0.0; 160_000], 16000);
+        let benchmark = engine.config().model_size.benchmark_realtime_factor();
+        assert_eq!(engine.estimate_time(&audio), Duration::from_secs_f64(10.0 * benchmark));
+
+        let mut result = TranscriptionResult::empty();
+        result.audio_duration_ms = 1000;
+        result.processing_time_ms = 500;
+        engine.record_realtime_factor(&result);
+        assert_eq!(engine.estimate_time(&audio), Duration::from_secs(5));
+
+        result.processing_time_ms = 1500;
+        engine.record_realtime_factor(&result);
+        assert!((engine.realtime_factor_estimate() - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_merge_channel_results() {
+        let left = TranscriptionResult {
+            text: "Hello there".to_string(),
+            segments: vec![
+                Segment::new(0, 1000, " Hello".to_string()),
+                Segment::new(2000, 3000, " there".to_string()),
+            ],
+            language: "en".to_string(),
+            processing_time_ms: 100,
+            inference_time_ms: 80,
+            audio_duration_ms: 3000,
+            processed_duration_ms: 3000,
+            ..TranscriptionResult::empty()
+        };
+        let right = TranscriptionResult {
+            text: "Hi".to_string(),
+            segments: vec![Segment::new(1000, 2000, " Hi".to_string())],
+            language: "en".to_string(),
+            processing_time_ms: 50,
+            inference_time_ms: 40,
+            audio_duration_ms: 3000,
+            processed_duration_ms: 3000,
+            ..TranscriptionResult::empty()
+        };
+
+        let merged = merge_channel_results(vec![left, right]);
+        let speakers: Vec<_> = merged.segments.iter().map(|s| s.speaker_id).collect();
+        assert_eq!(speakers, vec![Some(0), Some(1), Some(0)]);
+        assert_eq!(merged.text, "Hello Hi there");
+        assert_eq!(merged.processing_time_ms, 150);
+        assert_eq!(merged.inference_time_ms, 120);
+        assert_eq!(merged.audio_duration_ms, 3000);
+    }
+
     #[test]
     fn test_resample() {
         // Simple test: 48kHz to 16kHz should reduce length by 1/3
         let samples: Vec<f32> = (0..48000).map(|i| (i as f32 / 48000.0).sin()).collect();
-        let resampled = resample_to_16khz(&samples, 48000);
+        let resampled = resample_to_16khz(&samples, 48000, ResampleQuality::Linear).unwrap();
         assert_eq!(resampled.len(), 16000);
     }
 }
@@ -0,0 +1,234 @@
+//! Energy-based voice activity detection and silence trimming.
+
+use std::ops::Range;
+
+/// Analysis frame length in milliseconds.
+const FRAME_MS: usize = 30;
+
+/// Audio kept on each side of a speech region so onsets and tails survive.
+const PADDING_MS: usize = 300;
+
+/// Silences shorter than this are kept rather than cut.
+const MIN_SILENCE_MS: usize = 1000;
+
+/// Frame RMS counted as speech at a threshold of 1.0 (about -34 dBFS).
+///
+/// Lower thresholds scale it down linearly; 0.0 treats everything as speech.
+const FULL_SCALE_SPEECH_RMS: f32 = 0.02;
+
+/// Returns the sample ranges of `samples` that contain speech.
+///
+/// Ranges are padded, merged across short pauses, sorted and disjoint.
+pub fn detect_speech(samples: &[f32], sample_rate: u32, threshold: f32) -> Vec<Range<usize>> {
+    let ms_to_samples = |ms: usize| ms * sample_rate as usize / 1000;
+    let frame_len = ms_to_samples(FRAME_MS).max(1);
+    let padding = ms_to_samples(PADDING_MS);
+    let min_silence = ms_to_samples(MIN_SILENCE_MS);
+    let min_rms = threshold.clamp(0.0, 1.0) * FULL_SCALE_SPEECH_RMS;
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (index, frame) in samples.chunks(frame_len).enumerate() {
+        if frame_rms(frame) < min_rms {
+            continue;
+        }
+
+        let start = (index * frame_len).saturating_sub(padding);
+        let end = (index * frame_len + frame.len() + padding).min(samples.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end + min_silence => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+
+    ranges
+}
+
+/// Returns how many samples at the end of `samples` follow the last speech
+/// frame, or `None` if there is no speech at all.
+///
+/// Frames are aligned to the end of the buffer, so a newly pushed block of
+/// audio is analysed the same way however the stream was chunked.
+pub fn trailing_silence(samples: &[f32], sample_rate: u32, threshold: f32) -> Option<usize> {
+    let frame_len = (FRAME_MS * sample_rate as usize / 1000).max(1);
+    let min_rms = threshold.clamp(0.0, 1.0) * FULL_SCALE_SPEECH_RMS;
+
+    samples
+        .rchunks(frame_len)
+        .position(|frame| frame_rms(frame) >= min_rms)
+        .map(|frames| (frames * frame_len).min(samples.len()))
+}
+
+/// Returns the sample ranges of `samples` that are silent for at least
+/// `min_silence_ms`.
+///
+/// Uses the same frames and threshold as [`detect_speech`], but without
+/// padding, so ranges cover exactly the frames below the speech level.
+pub fn silence_regions(samples: &[f32], sample_rate: u32, threshold: f32, min_silence_ms: u32) -> Vec<Range<usize>> {
+    let frame_len = (FRAME_MS * sample_rate as usize / 1000).max(1);
+    let min_len = min_silence_ms as usize * sample_rate as usize / 1000;
+    let min_rms = threshold.clamp(0.0, 1.0) * FULL_SCALE_SPEECH_RMS;
+
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for (index, frame) in samples.chunks(frame_len).enumerate() {
+        if frame_rms(frame) >= min_rms {
+            continue;
+        }
+
+        let start = index * frame_len;
+        match regions.last_mut() {
+            Some(last) if last.end == start => last.end = start + frame.len(),
+            _ => regions.push(start..start + frame.len()),
+        }
+    }
+
+    regions.retain(|range| range.len() >= min_len);
+    regions
+}
+
+/// Root mean square of a frame.
+fn frame_rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// Concatenates the given ranges of `samples`.
+pub fn retain(samples: &[f32], ranges: &[Range<usize>]) -> Vec<f32> {
+    let mut retained = Vec::with_capacity(ranges.iter().map(|r| r.len()).sum());
+    for range in ranges {
+        retained.extend_from_slice(&samples[range.clone()]);
+    }
+    retained
+}
+
+/// A retained stretch of audio, in milliseconds.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    original_start_ms: i64,
+    trimmed_start_ms: i64,
+    duration_ms: i64,
+}
+
+/// Maps times on a trimmed timeline back to the original audio.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    spans: Vec<Span>,
+}
+
+impl Timeline {
+    /// Builds the mapping for audio reduced to `ranges` by [`retain`].
+    pub fn new(ranges: &[Range<usize>], sample_rate: u32) -> Self {
+        let to_ms = |samples: usize| (samples as u64 * 1000 / sample_rate as u64) as i64;
+        let mut trimmed_start_ms = 0;
+        let spans = ranges
+            .iter()
+            .map(|range| {
+                let span = Span {
+                    original_start_ms: to_ms(range.start),
+                    trimmed_start_ms,
+                    duration_ms: to_ms(range.end) - to_ms(range.start),
+                };
+                trimmed_start_ms += span.duration_ms;
+                span
+            })
+            .collect();
+        Self { spans }
+    }
+
+    /// Converts a trimmed-timeline time to original time.
+    ///
+    /// A time exactly at the junction of two spans maps to the start of the
+    /// later span, or to the end of the earlier one when `is_end` is set, so
+    /// segments never stretch across removed silence at their edges.
+    pub fn to_original_ms(&self, trimmed_ms: i64, is_end: bool) -> i64 {
+        let span = self
+            .spans
+            .iter()
+            .rev()
+            .find(|s| {
+                if is_end {
+                    s.trimmed_start_ms < trimmed_ms
+                } else {
+                    s.trimmed_start_ms <= trimmed_ms
+                }
+            })
+            .or(self.spans.first());
+
+        match span {
+            Some(span) => {
+                let offset = (trimmed_ms - span.trimmed_start_ms).clamp(0, span.duration_ms);
+                span.original_start_ms + offset
+            }
+            None => trimmed_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    /// Builds `total_ms` of silence with a tone over each range (in ms).
+    fn with_speech(total_ms: usize, speech: &[Range<usize>]) -> Vec<f32> {
+        let mut samples = vec![0.0; total_ms * 16];
+        for range in speech {
+            let (start, end) = (range.start * 16, range.end * 16);
+            for (i, sample) in samples[start..end].iter_mut().enumerate() {
+                *sample = ((start + i) as f32 * 0.1).sin() * 0.3;
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_detect_speech_pads_and_merges() {
+        let samples = with_speech(10_000, &[1000..2000, 5000..6000]);
+        let ranges = detect_speech(&samples, RATE, 0.5);
+        // Frame-aligned speech plus 300ms of padding on each side
+        assert_eq!(ranges, vec![11_040..36_960, 74_880..100_800]);
+
+        // Pauses under a second are bridged
+        let samples = with_speech(4000, &[1000..1500, 2000..2500]);
+        assert_eq!(detect_speech(&samples, RATE, 0.5).len(), 1);
+
+        assert!(detect_speech(&[0.0; 16_000], RATE, 0.5).is_empty());
+        assert_eq!(detect_speech(&[0.0; 16_000], RATE, 0.0), vec![0..16_000]);
+    }
+
+    #[test]
+    fn test_trailing_silence() {
+        let samples = with_speech(3000, &[500..1000, 1000..1500]);
+        // 1.5s of silence after speech, counted in whole 30ms frames
+        let silence = trailing_silence(&samples, RATE, 0.5).unwrap();
+        assert!((silence as i64 - 24_000).abs() <= 480, "{}", silence);
+
+        assert_eq!(trailing_silence(&with_speech(1000, &[0..500, 500..1000]), RATE, 0.5), Some(0));
+        assert_eq!(trailing_silence(&[0.0; 16_000], RATE, 0.5), None);
+    }
+
+    #[test]
+    fn test_silence_regions() {
+        let samples = with_speech(10_000, &[1000..2000, 5000..6000]);
+        // The 990ms lead-in is too short; bounds snap to 30ms frames
+        let regions = silence_regions(&samples, RATE, 0.5, 1000);
+        assert_eq!(regions, vec![32_160..79_680, 96_000..160_000]);
+
+        assert_eq!(silence_regions(&samples, RATE, 0.5, 0).len(), 3);
+        assert!(silence_regions(&samples, RATE, 0.0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_timeline_maps_to_original() {
+        let ranges = [16_000..32_000, 80_000..96_000];
+        let timeline = Timeline::new(&ranges, RATE);
+        assert_eq!(retain(&[0.0; 100_000], &ranges).len(), 32_000);
+
+        assert_eq!(timeline.to_original_ms(0, false), 1000);
+        assert_eq!(timeline.to_original_ms(500, false), 1500);
+        assert_eq!(timeline.to_original_ms(1000, false), 5000);
+        assert_eq!(timeline.to_original_ms(1000, true), 2000);
+        assert_eq!(timeline.to_original_ms(1001, true), 5001);
+        // Past the end clamps to the last retained sample
+        assert_eq!(timeline.to_original_ms(2500, true), 6000);
+    }
+}